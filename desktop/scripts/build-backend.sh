@@ -86,6 +86,33 @@ def write_startup_log(message: str):
     except Exception as e:
         print(f"[STARTUP ERROR] Failed to write log: {e}", flush=True)
 
+def start_detach_listener():
+    """Move stdout/stderr to a log file once the desktop app detaches from this process.
+
+    The app holds the read ends of our output pipes, so after it exits every write would fail
+    with EPIPE (or SIGPIPE). It writes "detach" to stdin before closing; EOF covers a crash.
+    """
+    import threading
+
+    def listen():
+        for line in sys.stdin:
+            if line.strip() == "detach":
+                break
+        log_file = open(get_log_dir() / "backend-detached.log", "a", encoding="utf-8")
+        sys.stdout.flush()
+        sys.stderr.flush()
+        pipe = os.dup(1)
+        os.dup2(log_file.fileno(), 1)
+        os.dup2(log_file.fileno(), 2)
+        # Acknowledge on the old pipe only after the switch, so nothing is written to it later
+        try:
+            os.write(pipe, b"OWORK_BACKEND_DETACHED\n")
+        except OSError:
+            pass
+        os.close(pipe)
+
+    threading.Thread(target=listen, daemon=True).start()
+
 def main():
     write_startup_log("Desktop backend starting...")
     write_startup_log(f"Python version: {sys.version}")
@@ -120,8 +147,13 @@ def main():
     parser = argparse.ArgumentParser(description="Claude Agent Platform Backend")
     parser.add_argument("--port", type=int, default=8000, help="Port to run on")
     parser.add_argument("--host", type=str, default="127.0.0.1", help="Host to bind to")
+    parser.add_argument("--concurrency", type=int, default=None, help="Maximum concurrent requests")
+    parser.add_argument("--detachable", action="store_true", help="Keep running when the app detaches")
     args = parser.parse_args()
 
+    if args.detachable:
+        start_detach_listener()
+
     write_startup_log(f"Starting server on {args.host}:{args.port}")
     print(f"Starting backend server on {args.host}:{args.port}", flush=True)
 
@@ -136,6 +168,7 @@ def main():
             loop="asyncio",  # Use asyncio loop explicitly
             reload=False,    # Disable reload in bundled app
             workers=1,       # Single worker for bundled app
+            limit_concurrency=args.concurrency,
         )
         write_startup_log("Uvicorn config created successfully")
 
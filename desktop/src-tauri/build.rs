@@ -1,3 +1,129 @@
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Convert days since the Unix epoch to a (year, month, day) civil date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// Collect (bundle path, size, sha256) for every file under path (a file or a directory),
+// where dest is the path the bundler gives path inside the resource dir
+fn collect_bundled_files(path: &Path, dest: &Path, files: &mut Vec<(String, u64, String)>) {
+    if path.is_dir() {
+        let entries = std::fs::read_dir(path)
+            .unwrap_or_else(|e| panic!("failed to read bundled resource dir {}: {}", path.display(), e));
+        for entry in entries.flatten() {
+            collect_bundled_files(&entry.path(), &dest.join(entry.file_name()), files);
+        }
+    } else {
+        let contents = std::fs::read(path)
+            .unwrap_or_else(|e| panic!("failed to read bundled resource {}: {}", path.display(), e));
+        let hash = Sha256::digest(&contents)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        files.push((dest.to_string_lossy().replace('\\', "/"), contents.len() as u64, hash));
+    }
+}
+
+// Where the bundler places a resource listed as a relative source path: `..` becomes `_up_`
+// (mirrors tauri_utils::resources::resource_relpath)
+fn resource_relpath(path: &Path) -> PathBuf {
+    let mut dest = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) => {}
+            Component::RootDir => dest.push("_root_"),
+            Component::CurDir => {}
+            Component::ParentDir => dest.push("_up_"),
+            Component::Normal(part) => dest.push(part),
+        }
+    }
+    dest
+}
+
+// Embed the manifest of bundled resources so verify_bundled_files can detect corrupt installs
+// Covers the files and directories listed in bundle.resources, recorded at their path inside
+// the installed resource dir; a listed path that doesn't exist fails the build
+fn write_bundled_manifest() {
+    println!("cargo:rerun-if-changed=tauri.conf.json");
+    let config: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string("tauri.conf.json").expect("failed to read tauri.conf.json"),
+    )
+    .expect("failed to parse tauri.conf.json");
+
+    // Either a list of source paths or a map of source path to target path
+    let resources: Vec<(String, PathBuf)> = match &config["bundle"]["resources"] {
+        serde_json::Value::Array(sources) => sources
+            .iter()
+            .filter_map(|source| source.as_str())
+            .map(|source| (source.to_string(), resource_relpath(Path::new(source))))
+            .collect(),
+        serde_json::Value::Object(map) => map
+            .iter()
+            .filter_map(|(source, target)| Some((source.clone(), PathBuf::from(target.as_str()?))))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut files = Vec::new();
+    for (source, dest) in &resources {
+        if source.contains(['*', '?', '[']) {
+            panic!("bundle.resources entry {:?} is a glob; list the file or directory instead", source);
+        }
+        let path = Path::new(source);
+        if !path.exists() {
+            panic!("bundle.resources entry {:?} does not exist", source);
+        }
+        println!("cargo:rerun-if-changed={}", source);
+        collect_bundled_files(path, dest, &mut files);
+    }
+    files.sort();
+
+    let mut manifest = String::from("pub const BUNDLED_FILES: &[(&str, u64, &str)] = &[\n");
+    for (path, size, hash) in &files {
+        manifest.push_str(&format!("    ({:?}, {}, {:?}),\n", path, size, hash));
+    }
+    manifest.push_str("];\n");
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    std::fs::write(Path::new(&out_dir).join("bundled_manifest.rs"), manifest)
+        .expect("failed to write bundled manifest");
+}
+
 fn main() {
+    // Embed the git commit hash and build date for get_app_version
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=OWORK_GIT_COMMIT={}", commit);
+
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    println!("cargo:rustc-env=OWORK_BUILD_DATE={:04}-{:02}-{:02}", year, month, day);
+
+    write_bundled_manifest();
+
+    println!("cargo:rerun-if-changed=../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../.git/refs");
+
     tauri_build::build()
 }
@@ -0,0 +1,460 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+// Default per-request timeout for health checks
+pub const HEALTH_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Delay between readiness polls while the backend is starting
+const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// Build the URL of a backend endpoint, e.g. http://127.0.0.1:8123/health
+pub fn endpoint_url(scheme: &str, host: &str, port: u16, path: &str) -> String {
+    // IPv6 literals must be bracketed in URLs
+    if host.contains(':') && !host.starts_with('[') {
+        format!("{}://[{}]:{}{}", scheme, host, port, path)
+    } else {
+        format!("{}://{}:{}{}", scheme, host, port, path)
+    }
+}
+
+fn is_loopback_url(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_matches(['[', ']'])
+            .parse::<std::net::IpAddr>()
+            .map(|ip| ip.is_loopback())
+            .unwrap_or(false)
+}
+
+// HTTP client for talking to the backend at url
+// Certificate verification is skipped for loopback hosts, where a local TLS setup
+// will almost always use a self-signed certificate
+fn client(url: &str, timeout: Duration) -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .danger_accept_invalid_certs(is_loopback_url(url))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+// Send a single health request, returning Ok(true) on a 2xx response
+// If expected_status is set, the response must also be JSON with a matching "status" field,
+// so an unrelated service that happens to answer on the port isn't mistaken for the backend
+// Timeouts and refused connections get distinct messages ("slow but alive" vs "not listening")
+pub async fn check_health(
+    url: &str,
+    expected_status: Option<&str>,
+    timeout: Duration,
+) -> Result<bool, String> {
+    check_health_with(&client(url, timeout)?, url, expected_status, timeout).await
+}
+
+// check_health with a client built by the caller, so repeated polls can share one
+async fn check_health_with(
+    client: &reqwest::Client,
+    url: &str,
+    expected_status: Option<&str>,
+    timeout: Duration,
+) -> Result<bool, String> {
+    let response = client.get(url).send().await.map_err(|e| {
+        if e.is_timeout() {
+            format!(
+                "Health check timed out after {}ms; the backend may be busy",
+                timeout.as_millis()
+            )
+        } else if e.is_connect() {
+            format!("Connection refused: nothing is listening at {}", url)
+        } else {
+            format!("Health check failed: {}", e)
+        }
+    })?;
+
+    if !response.status().is_success() {
+        return Ok(false);
+    }
+
+    let Some(expected_status) = expected_status else {
+        return Ok(true);
+    };
+
+    let is_json = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.starts_with("application/json"))
+        .unwrap_or(false);
+    if !is_json {
+        return Err(format!(
+            "{} did not return JSON; another service may be using the backend port",
+            url
+        ));
+    }
+
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid health response: {}", e))?;
+    match body.get("status").and_then(|status| status.as_str()) {
+        Some(status) if status == expected_status => Ok(true),
+        status => Err(format!(
+            "Unexpected health status {:?} (expected {:?}); another service may be using the backend port",
+            status, expected_status
+        )),
+    }
+}
+
+// Poll the health endpoint until it reports healthy or the startup timeout elapses
+// `stop_waiting` lets the caller end the wait early (returning Ok), e.g. when the backend
+// announced readiness itself or already exited
+pub async fn wait_until_ready(
+    url: &str,
+    expected_status: Option<&str>,
+    request_timeout: Duration,
+    startup_timeout: Duration,
+    stop_waiting: impl Fn() -> bool,
+) -> Result<(), String> {
+    let deadline = Instant::now() + startup_timeout;
+    let client = client(url, request_timeout)?;
+    loop {
+        if stop_waiting() {
+            return Ok(());
+        }
+        let last_error = match check_health_with(&client, url, expected_status, request_timeout).await {
+            Ok(true) => return Ok(()),
+            Ok(false) => "health endpoint returned an error status".to_string(),
+            Err(e) => e,
+        };
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Backend not ready after {}s: {}",
+                startup_timeout.as_secs(),
+                last_error
+            ));
+        }
+        tokio::time::sleep(READINESS_POLL_INTERVAL).await;
+    }
+}
+
+// Ask the backend to shut itself down, e.g. via POST /shutdown
+pub async fn request_shutdown(url: &str, timeout: Duration) -> Result<(), String> {
+    let response = client(url, timeout)?
+        .post(url)
+        .send()
+        .await
+        .map_err(|e| format!("Shutdown request failed: {}", e))?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("Shutdown request returned {}", response.status()))
+    }
+}
+
+// POST a JSON body to a backend control endpoint
+// Returns Ok(false) if the backend doesn't support the endpoint (404/405/501)
+pub async fn post_json(url: &str, body: &serde_json::Value, timeout: Duration) -> Result<bool, String> {
+    let response = client(url, timeout)?
+        .post(url)
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+
+    let status = response.status();
+    if status.is_success() {
+        Ok(true)
+    } else if matches!(status.as_u16(), 404 | 405 | 501) {
+        Ok(false)
+    } else {
+        Err(format!("{} returned {}", url, status))
+    }
+}
+
+// GET a JSON document from the backend
+pub async fn get_json(url: &str, timeout: Duration) -> Result<serde_json::Value, String> {
+    let response = client(url, timeout)?
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("{} returned {}", url, response.status()));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid JSON from {}: {}", url, e))
+}
+
+#[derive(Serialize)]
+pub struct ClockSkew {
+    skew_ms: i64,        // Backend clock minus local clock; positive = backend is ahead
+    round_trip_ms: u64,
+    resolution_ms: u64,  // The HTTP Date header only has second resolution
+}
+
+// Estimate the backend's clock offset from the Date header of a response at url,
+// assuming the header was stamped halfway through the round trip
+pub async fn clock_skew(url: &str, timeout: Duration) -> Result<ClockSkew, String> {
+    let client = client(url, timeout)?;
+    let sent_at = std::time::SystemTime::now();
+    let started = Instant::now();
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+    let round_trip = started.elapsed();
+
+    let date = response
+        .headers()
+        .get(reqwest::header::DATE)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| format!("{} did not send a Date header", url))?;
+    let backend_time =
+        httpdate::parse_http_date(date).map_err(|e| format!("Invalid Date header {:?}: {}", date, e))?;
+
+    let local_time = sent_at + round_trip / 2;
+    let skew_ms = match backend_time.duration_since(local_time) {
+        Ok(ahead) => ahead.as_millis() as i64,
+        Err(behind) => -(behind.duration().as_millis() as i64),
+    };
+    Ok(ClockSkew {
+        skew_ms,
+        round_trip_ms: round_trip.as_millis() as u64,
+        resolution_ms: 1000,
+    })
+}
+
+#[derive(Serialize)]
+pub struct LatencyStats {
+    requests: u32,
+    failures: u32,
+    min_ms: f64,
+    median_ms: f64,
+    p95_ms: f64,
+    max_ms: f64,
+}
+
+// Nearest-rank percentile of an ascending-sorted, non-empty slice
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = ((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1]
+}
+
+// Fire `requests` health requests split across `concurrency` sequential workers
+// Only successful (2xx) requests count towards the latency figures
+pub async fn benchmark(url: &str, requests: u32, concurrency: u32) -> Result<LatencyStats, String> {
+    let client = client(url, HEALTH_TIMEOUT)?;
+    let concurrency = concurrency.clamp(1, requests.max(1));
+
+    let mut workers = Vec::new();
+    for worker in 0..concurrency {
+        // Spread the remainder over the first workers
+        let share = requests / concurrency + u32::from(worker < requests % concurrency);
+        let client = client.clone();
+        let url = url.to_string();
+        workers.push(tauri::async_runtime::spawn(async move {
+            let mut samples = Vec::with_capacity(share as usize);
+            let mut failures = 0u32;
+            for _ in 0..share {
+                let started = Instant::now();
+                match client.get(&url).send().await {
+                    Ok(response) if response.status().is_success() => {
+                        samples.push(started.elapsed().as_secs_f64() * 1000.0);
+                    }
+                    _ => failures += 1,
+                }
+            }
+            (samples, failures)
+        }));
+    }
+
+    let mut samples = Vec::with_capacity(requests as usize);
+    let mut failures = 0;
+    for worker in workers {
+        let (worker_samples, worker_failures) = worker
+            .await
+            .map_err(|e| format!("Benchmark worker failed: {}", e))?;
+        samples.extend(worker_samples);
+        failures += worker_failures;
+    }
+
+    if samples.is_empty() {
+        return Err(format!("All {} health requests failed", requests));
+    }
+
+    samples.sort_by(|a, b| a.total_cmp(b));
+    Ok(LatencyStats {
+        requests,
+        failures,
+        min_ms: samples[0],
+        median_ms: percentile(&samples, 0.5),
+        p95_ms: percentile(&samples, 0.95),
+        max_ms: samples[samples.len() - 1],
+    })
+}
+
+// Whether a Content Security Policy lets fetch()/XHR reach `url`
+// Returns the governing directive (connect-src, else default-src; None = unrestricted) and the verdict
+pub fn csp_allows_connect(policy: &str, url: &str) -> (Option<String>, bool) {
+    let directive = ["connect-src", "default-src"].iter().find_map(|name| {
+        policy
+            .split(';')
+            .map(str::trim)
+            .find(|directive| directive.split_whitespace().next() == Some(*name))
+    });
+    let Some(directive) = directive else {
+        return (None, true);
+    };
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return (Some(directive.to_string()), false);
+    };
+    let (scheme, host, port) = (
+        url.scheme(),
+        url.host_str().unwrap_or_default(),
+        url.port_or_known_default().unwrap_or_default(),
+    );
+
+    let allowed = directive.split_whitespace().skip(1).any(|source| {
+        // '*' covers every network scheme; 'self' etc. refer to the webview's own origin
+        if source == "*" {
+            return true;
+        }
+        if let Some(source_scheme) = source.strip_suffix(':') {
+            return source_scheme.eq_ignore_ascii_case(scheme);
+        }
+        if source.starts_with('\'') {
+            return false;
+        }
+
+        let rest = match source.split_once("://") {
+            Some((source_scheme, rest)) => {
+                // An http: source also allows the upgraded https: URL
+                let matches = source_scheme.eq_ignore_ascii_case(scheme)
+                    || (source_scheme.eq_ignore_ascii_case("http") && scheme == "https");
+                if !matches {
+                    return false;
+                }
+                rest
+            }
+            None => source,
+        };
+        let authority = rest.split('/').next().unwrap_or_default();
+        let (source_host, source_port) = match authority.rsplit_once(':') {
+            // "[::1]" has colons but no port
+            Some((source_host, source_port)) if !source_port.contains(']') => (source_host, Some(source_port)),
+            _ => (authority, None),
+        };
+
+        let host_matches = match source_host.strip_prefix("*.") {
+            Some(suffix) => host.to_lowercase().ends_with(&format!(".{}", suffix.to_lowercase())),
+            None => source_host.eq_ignore_ascii_case(host),
+        };
+        let port_matches = match source_port {
+            Some("*") => true,
+            Some(source_port) => source_port.parse::<u16>() == Ok(port),
+            None => matches!((scheme, port), ("http", 80) | ("https", 443)),
+        };
+        host_matches && port_matches
+    });
+    (Some(directive.to_string()), allowed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allows(policy: &str, url: &str) -> bool {
+        csp_allows_connect(policy, url).1
+    }
+
+    #[test]
+    fn csp_without_a_governing_directive_is_unrestricted() {
+        assert_eq!(csp_allows_connect("img-src 'self'", "http://127.0.0.1:8000"), (None, true));
+        assert_eq!(csp_allows_connect("", "http://127.0.0.1:8000"), (None, true));
+    }
+
+    #[test]
+    fn csp_falls_back_from_connect_src_to_default_src() {
+        assert_eq!(
+            csp_allows_connect("default-src 'self' http://127.0.0.1:8000", "http://127.0.0.1:8000/health"),
+            (Some("default-src 'self' http://127.0.0.1:8000".to_string()), true)
+        );
+        // connect-src takes precedence even when default-src would allow the URL
+        assert_eq!(
+            csp_allows_connect("default-src *; connect-src 'self'", "http://127.0.0.1:8000"),
+            (Some("connect-src 'self'".to_string()), false)
+        );
+    }
+
+    #[test]
+    fn csp_wildcard_allows_everything() {
+        assert!(allows("connect-src *", "http://127.0.0.1:8000"));
+        assert!(allows("connect-src 'self' *", "https://example.com"));
+    }
+
+    #[test]
+    fn csp_scheme_only_source_matches_the_scheme() {
+        assert!(allows("connect-src http:", "http://127.0.0.1:8000"));
+        assert!(allows("connect-src HTTP:", "http://localhost:1234"));
+        assert!(!allows("connect-src https:", "http://127.0.0.1:8000"));
+        assert!(!allows("connect-src 'self'", "http://127.0.0.1:8000"));
+    }
+
+    #[test]
+    fn csp_host_wildcard_matches_subdomains_only() {
+        assert!(allows("connect-src https://*.example.com", "https://api.example.com"));
+        assert!(allows("connect-src https://*.example.com", "https://a.b.Example.com"));
+        assert!(!allows("connect-src https://*.example.com", "https://example.com"));
+        assert!(!allows("connect-src https://*.example.com", "https://badexample.com"));
+    }
+
+    #[test]
+    fn csp_port_must_match() {
+        assert!(allows("connect-src http://localhost:*", "http://localhost:8000"));
+        assert!(allows("connect-src http://localhost:8000", "http://localhost:8000/health"));
+        assert!(!allows("connect-src http://localhost:8000", "http://localhost:8001"));
+    }
+
+    #[test]
+    fn csp_source_without_port_only_allows_the_default_port() {
+        assert!(allows("connect-src http://localhost", "http://localhost/health"));
+        assert!(allows("connect-src https://example.com", "https://example.com:443"));
+        assert!(!allows("connect-src http://localhost", "http://localhost:8000"));
+        // A host-only source applies to any scheme, still at that scheme's default port
+        assert!(allows("connect-src localhost", "http://localhost"));
+        assert!(!allows("connect-src localhost", "http://localhost:8000"));
+    }
+
+    #[test]
+    fn csp_http_source_allows_the_https_upgrade() {
+        assert!(allows("connect-src http://127.0.0.1:8000", "https://127.0.0.1:8000"));
+        assert!(!allows("connect-src https://127.0.0.1:8000", "http://127.0.0.1:8000"));
+        assert!(!allows("connect-src ws://127.0.0.1:8000", "http://127.0.0.1:8000"));
+    }
+
+    #[test]
+    fn csp_matches_bracketed_ipv6_hosts() {
+        assert!(allows("connect-src http://[::1]:8000", "http://[::1]:8000/health"));
+        assert!(allows("connect-src http://[::1]", "http://[::1]/health"));
+        assert!(!allows("connect-src http://[::1]", "http://[::1]:8000"));
+        assert!(!allows("connect-src http://[::1]:8000", "http://127.0.0.1:8000"));
+    }
+
+    #[test]
+    fn csp_rejects_an_unparseable_url() {
+        assert_eq!(
+            csp_allows_connect("connect-src *", "not a url"),
+            (Some("connect-src *".to_string()), false)
+        );
+    }
+}
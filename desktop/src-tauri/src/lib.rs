@@ -1,30 +1,178 @@
 use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::env;
+use std::time::{Duration, Instant};
 use tauri::{Emitter, Manager};
 use tauri_plugin_shell::ShellExt;
 use tauri_plugin_shell::process::CommandChild;
 use tokio::sync::Mutex;
 
+mod health;
+mod limits;
+mod logs;
+mod process;
+mod secrets;
+mod settings;
+mod snapshot;
+#[cfg(debug_assertions)]
+mod watcher;
+
+// Manifest of bundled resource files (path, size, sha256) generated by build.rs
+mod bundled {
+    include!(concat!(env!("OUT_DIR"), "/bundled_manifest.rs"));
+}
+
+use logs::SharedLogBuffer;
+use settings::SharedSettings;
+
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
-// Get enhanced PATH that includes common installation locations for the sidecar
-fn get_enhanced_path() -> String {
-    let current_path = env::var("PATH").unwrap_or_default();
+#[cfg(target_os = "windows")]
+const PATH_SEPARATOR: &str = ";";
+
+#[cfg(not(target_os = "windows"))]
+const PATH_SEPARATOR: &str = ":";
+
+// Python environment the app was launched from (e.g. an activated virtualenv or conda env)
+#[derive(Clone, Serialize)]
+pub struct PythonEnv {
+    virtual_env: Option<String>,
+    conda_prefix: Option<String>,
+}
+
+fn detect_python_env() -> PythonEnv {
+    let non_empty = |name: &str| env::var(name).ok().filter(|value| !value.trim().is_empty());
+
+    PythonEnv {
+        virtual_env: non_empty("VIRTUAL_ENV"),
+        conda_prefix: non_empty("CONDA_PREFIX"),
+    }
+}
+
+// Executable dirs of the active Python environment, highest priority first
+fn python_env_bin_dirs(python_env: &PythonEnv) -> Vec<String> {
+    let mut dirs = Vec::new();
 
     #[cfg(target_os = "windows")]
-    let (home, path_separator) = (
-        env::var("USERPROFILE").unwrap_or_default(),
-        ";"
-    );
+    {
+        if let Some(venv) = &python_env.virtual_env {
+            dirs.push(format!(r"{}\Scripts", venv));
+        }
+        if let Some(conda) = &python_env.conda_prefix {
+            dirs.push(conda.clone());
+            dirs.push(format!(r"{}\Scripts", conda));
+            dirs.push(format!(r"{}\Library\bin", conda));
+        }
+    }
 
     #[cfg(not(target_os = "windows"))]
-    let (home, path_separator) = (
-        env::var("HOME").unwrap_or_default(),
-        ":"
+    {
+        if let Some(venv) = &python_env.virtual_env {
+            dirs.push(format!("{}/bin", venv));
+        }
+        if let Some(conda) = &python_env.conda_prefix {
+            dirs.push(format!("{}/bin", conda));
+        }
+    }
+
+    dirs
+}
+
+// Get enhanced PATH entries, in resolution order, that include common installation locations for the sidecar
+fn get_enhanced_path_entries() -> Vec<String> {
+    scan_enhanced_path_entries().0
+}
+
+// Bin dir checks each scan thread takes on; smaller scans aren't worth a thread
+#[cfg(not(target_os = "windows"))]
+const BIN_CHECKS_PER_THREAD: usize = 16;
+
+// Subdirectories of `parent` whose name matches `filter` and that contain a bin dir, sorted by name
+// `scanned` counts the directories touched, for the PATH scan diagnostics
+// The bin dir checks dominate with hundreds of Homebrew packages or nvm versions, so they are
+// spread over scoped threads; chunks are joined in order, so the result doesn't depend on timing
+#[cfg(not(target_os = "windows"))]
+fn scan_bin_dirs(parent: &str, filter: impl Fn(&str) -> bool, scanned: &mut usize) -> Vec<String> {
+    *scanned += 1;
+    let Ok(entries) = std::fs::read_dir(parent) else {
+        return Vec::new();
+    };
+    let mut candidates: Vec<std::path::PathBuf> = entries
+        .flatten()
+        .filter(|entry| filter(&entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path().join("bin"))
+        .collect();
+    candidates.sort();
+    *scanned += candidates.len();
+
+    let check = |chunk: &[std::path::PathBuf]| -> Vec<String> {
+        chunk
+            .iter()
+            .filter(|bin_path| bin_path.exists())
+            .map(|bin_path| bin_path.to_string_lossy().to_string())
+            .collect()
+    };
+    let threads = candidates
+        .len()
+        .div_ceil(BIN_CHECKS_PER_THREAD)
+        .min(std::thread::available_parallelism().map_or(1, |n| n.get()));
+    if threads <= 1 {
+        return check(&candidates);
+    }
+
+    let chunk_size = candidates.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = candidates
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || check(chunk)))
+            .collect();
+        // A panicked check just contributes nothing
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+// Build the enhanced PATH entries, also returning how many directories were scanned
+fn scan_enhanced_path_entries() -> (Vec<String>, usize) {
+    let current_path = env::var("PATH").unwrap_or_default();
+
+    // An activated virtualenv/conda env wins over everything else, as it would in the user's terminal
+    let mut paths = python_env_bin_dirs(&detect_python_env());
+
+    let (toolchain_dirs, scanned) = scan_toolchain_path_entries();
+    paths.extend(toolchain_dirs);
+
+    // The inherited PATH goes last so the well-known locations above take precedence
+    paths.extend(
+        current_path
+            .split(PATH_SEPARATOR)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| entry.to_string()),
     );
 
+    let entries = dedup_path_entries(paths);
+    log::debug!("Enhanced PATH: {} entries, {} toolchain dirs scanned", entries.len(), scanned);
+    (entries, scanned)
+}
+
+// The well-known toolchain locations the enhanced PATH adds, in priority order, plus how many
+// directories were scanned. Unlike the inherited PATH and an activated env, these don't depend
+// on how the app was launched (Finder vs terminal)
+fn scan_toolchain_path_entries() -> (Vec<String>, usize) {
+    #[cfg(target_os = "windows")]
+    let home = env::var("USERPROFILE").unwrap_or_default();
+
+    #[cfg(not(target_os = "windows"))]
+    let home = env::var("HOME").unwrap_or_default();
+
+    #[cfg_attr(target_os = "windows", allow(unused_mut))]
+    let mut scanned = 0;
+
     let mut paths = Vec::new();
 
     // Platform-specific common paths
@@ -46,19 +194,12 @@ fn get_enhanced_path() -> String {
         // These packages are installed to /opt/homebrew/opt/node@XX/bin/ on Apple Silicon
         // or /usr/local/opt/node@XX/bin/ on Intel Mac
         for homebrew_opt in &["/opt/homebrew/opt", "/usr/local/opt"] {
-            if let Ok(entries) = std::fs::read_dir(homebrew_opt) {
-                for entry in entries.flatten() {
-                    let name = entry.file_name();
-                    let name_str = name.to_string_lossy();
-                    // Match node, node@XX, python, python@XX patterns
-                    if name_str.starts_with("node") || name_str.starts_with("python") {
-                        let bin_path = entry.path().join("bin");
-                        if bin_path.exists() {
-                            paths.push(bin_path.to_string_lossy().to_string());
-                        }
-                    }
-                }
-            }
+            // Match node, node@XX, python, python@XX patterns
+            paths.extend(scan_bin_dirs(
+                homebrew_opt,
+                |name| name.starts_with("node") || name.starts_with("python"),
+                &mut scanned,
+            ));
         }
     }
 
@@ -108,14 +249,7 @@ fn get_enhanced_path() -> String {
 
         // For nvm, we need to find actual node version directories
         let nvm_dir = format!("{}/.nvm/versions/node", home);
-        if let Ok(entries) = std::fs::read_dir(&nvm_dir) {
-            for entry in entries.flatten() {
-                let bin_path = entry.path().join("bin");
-                if bin_path.exists() {
-                    paths.push(bin_path.to_string_lossy().to_string());
-                }
-            }
-        }
+        paths.extend(scan_bin_dirs(&nvm_dir, |_| true, &mut scanned));
     }
 
     #[cfg(target_os = "windows")]
@@ -130,11 +264,275 @@ fn get_enhanced_path() -> String {
         }
     }
 
-    if !current_path.is_empty() {
-        paths.push(current_path);
+    (dedup_path_entries(paths), scanned)
+}
+
+// Cheap canonical form of a PATH entry: trimmed, without trailing separators
+// (and with backslashes on Windows). Avoids fs::canonicalize, which hits the disk
+// and produces \\?\ paths on Windows.
+fn normalize_path_entry(entry: &str) -> String {
+    let entry = entry.trim();
+
+    #[cfg(target_os = "windows")]
+    let entry = entry.replace('/', "\\");
+
+    #[cfg(target_os = "windows")]
+    let trimmed = entry.trim_end_matches('\\');
+
+    #[cfg(not(target_os = "windows"))]
+    let trimmed = entry.trim_end_matches('/');
+
+    // Keep root directories ("/" or "C:\") intact
+    if trimmed.is_empty() || trimmed.ends_with(':') {
+        entry.to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+// Remove duplicate PATH entries, keeping the first occurrence so resolution order is preserved
+// Comparison is case-insensitive on Windows, where the filesystem is too
+fn dedup_path_entries(entries: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    let mut result = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let entry = normalize_path_entry(&entry);
+        if entry.is_empty() {
+            continue;
+        }
+
+        #[cfg(target_os = "windows")]
+        let key = entry.to_lowercase();
+
+        #[cfg(not(target_os = "windows"))]
+        let key = entry.clone();
+
+        if seen.insert(key) {
+            result.push(entry);
+        }
+    }
+
+    result
+}
+
+// Get enhanced PATH that includes common installation locations for the sidecar
+fn get_enhanced_path() -> String {
+    get_enhanced_path_entries().join(PATH_SEPARATOR)
+}
+
+// Windows caps environment variable values at 32767 characters; stay safely below that
+#[cfg(target_os = "windows")]
+const WINDOWS_MAX_PATH_LENGTH: usize = 32_000;
+
+// Drop the lowest-priority directories we added until the joined PATH fits within max_len
+// The inherited PATH holds the system directories, so it is kept; only when it is too long on
+// its own are its last entries dropped as well. Returns the dropped entries
+#[cfg(target_os = "windows")]
+fn trim_path_to_limit(entries: &mut Vec<String>, inherited: &str, max_len: usize) -> Vec<String> {
+    let joined_len = |entries: &[String]| {
+        entries.iter().map(|e| e.len()).sum::<usize>() + entries.len().saturating_sub(1)
+    };
+    let inherited: std::collections::HashSet<String> = inherited
+        .split(PATH_SEPARATOR)
+        .map(|entry| normalize_path_entry(entry).to_lowercase())
+        .collect();
+
+    let mut dropped = Vec::new();
+    let mut index = entries.len();
+    while index > 0 && joined_len(entries) > max_len {
+        index -= 1;
+        if !inherited.contains(&normalize_path_entry(&entries[index]).to_lowercase()) {
+            dropped.push(entries.remove(index));
+        }
+    }
+    while entries.len() > 1 && joined_len(entries) > max_len {
+        if let Some(entry) = entries.pop() {
+            dropped.push(entry);
+        }
+    }
+    dropped
+}
+
+// Tools the backend runs itself; the minimal PATH keeps only the directories providing them
+const REQUIRED_TOOLS: [&str; 4] = ["node", "python3", "python", "git"];
+
+// System directories kept in the minimal PATH so shells and basic utilities still resolve
+#[cfg(target_os = "windows")]
+fn system_path_dirs() -> Vec<String> {
+    let system_root = env::var("SystemRoot").unwrap_or_else(|_| r"C:\Windows".to_string());
+    vec![format!(r"{}\System32", system_root), system_root]
+}
+
+#[cfg(not(target_os = "windows"))]
+fn system_path_dirs() -> Vec<String> {
+    vec!["/usr/bin".to_string(), "/bin".to_string()]
+}
+
+// Last-resort PATH for stripped-down launch environments (cron, service managers) where
+// neither the inherited PATH nor the home-relative heuristics yield a usable directory
+#[cfg(target_os = "windows")]
+fn default_system_path_dirs() -> Vec<String> {
+    let system_root = env::var("SystemRoot").unwrap_or_else(|_| r"C:\Windows".to_string());
+    let mut dirs = system_path_dirs();
+    dirs.push(format!(r"{}\System32\Wbem", system_root));
+    dirs.push(format!(r"{}\System32\WindowsPowerShell\v1.0", system_root));
+    dirs
+}
+
+#[cfg(not(target_os = "windows"))]
+fn default_system_path_dirs() -> Vec<String> {
+    ["/usr/local/bin", "/usr/bin", "/bin", "/usr/sbin", "/sbin"]
+        .iter()
+        .map(|dir| dir.to_string())
+        .collect()
+}
+
+// Whether no PATH entry is an existing directory, so nothing could be found on it
+fn is_path_effectively_empty(entries: &[String]) -> bool {
+    !entries
+        .iter()
+        .map(std::path::Path::new)
+        .any(|dir| dir.is_absolute() && dir.is_dir())
+}
+
+// Only the enhanced PATH directories that contain a required tool (in priority order),
+// followed by the essential system directories
+fn get_minimal_path_entries() -> Vec<String> {
+    let entries = get_enhanced_path_entries();
+
+    let mut minimal: Vec<String> = entries
+        .iter()
+        .filter(|dir| {
+            REQUIRED_TOOLS
+                .iter()
+                .any(|tool| find_executable(tool, std::slice::from_ref(*dir)).is_some())
+        })
+        .cloned()
+        .collect();
+    minimal.extend(system_path_dirs());
+    dedup_path_entries(minimal)
+}
+
+// PATH entries for the sidecar, plus any entries dropped to stay within platform limits
+// With `minimal`, only directories containing the tools the backend needs are included
+fn sidecar_path_entries(minimal: bool) -> (Vec<String>, Vec<String>) {
+    #[cfg_attr(not(target_os = "windows"), allow(unused_mut))]
+    let mut entries = if minimal {
+        get_minimal_path_entries()
+    } else {
+        get_enhanced_path_entries()
+    };
+
+    // Heavily-configured Windows machines can exceed the environment variable length limit,
+    // which makes the spawn fail, so drop the lowest-priority entries instead
+    #[cfg(target_os = "windows")]
+    let dropped = trim_path_to_limit(&mut entries, &env::var("PATH").unwrap_or_default(), WINDOWS_MAX_PATH_LENGTH);
+
+    #[cfg(not(target_os = "windows"))]
+    let dropped = Vec::new();
+
+    (entries, dropped)
+}
+
+// sidecar_path_entries with launch_backend's fallback to the default system directories when
+// none of the entries is usable, for commands reporting on the PATH the sidecar gets
+fn usable_sidecar_path_entries(minimal: bool) -> (Vec<String>, Vec<String>) {
+    let (entries, dropped) = sidecar_path_entries(minimal);
+    if is_path_effectively_empty(&entries) {
+        (default_system_path_dirs(), dropped)
+    } else {
+        (entries, dropped)
+    }
+}
+
+// Environment variables set on the sidecar on top of the environment it inherits from the app
+fn sidecar_env(path_entries: &[String]) -> Vec<(String, String)> {
+    let mut vars = vec![("PATH".to_string(), path_entries.join(PATH_SEPARATOR))];
+
+    // Forward the active virtualenv/conda env so the backend uses the same interpreter setup
+    let python_env = detect_python_env();
+    if let Some(venv) = python_env.virtual_env {
+        vars.push(("VIRTUAL_ENV".to_string(), venv));
+    }
+    if let Some(conda) = python_env.conda_prefix {
+        vars.push(("CONDA_PREFIX".to_string(), conda));
+    }
+
+    vars
+}
+
+// The full environment set on the sidecar: its PATH and Python env, the tools it can rely on,
+// the variables derived from the settings, and the backend token when there is one. Everything that spawns the sidecar or
+// reports its environment builds it here, so they can't disagree
+fn backend_env(
+    path_entries: &[String],
+    settings: &settings::BackendSettings,
+    token: Option<&str>,
+) -> Vec<(String, String)> {
+    let mut vars = sidecar_env(path_entries);
+    let (capabilities, _) = detect_capabilities(path_entries);
+    vars.push(("OWORK_CAPABILITIES".to_string(), capabilities.join(",")));
+    vars.extend(
+        settings::sidecar_env(settings)
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value)),
+    );
+    if let Some(token) = token {
+        vars.push((secrets::BACKEND_TOKEN_ENV.to_string(), token.to_string()));
+    }
+    vars
+}
+
+// Whether an environment variable name suggests a secret that must not be exported
+fn is_sensitive_env_var(name: &str) -> bool {
+    const MARKERS: [&str; 8] = [
+        "KEY", "TOKEN", "SECRET", "PASSWORD", "PASSWD", "CREDENTIAL", "AUTH", "COOKIE",
+    ];
+    let name = name.to_uppercase();
+    MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+// Check whether a path is an executable file
+fn is_executable(path: &std::path::Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+
+    #[cfg(not(unix))]
+    {
+        path.is_file()
     }
+}
+
+// Resolve an executable name against the given PATH entries, like `which`
+// On Windows, names without an extension are tried with each PATHEXT extension
+fn find_executable(name: &str, path_entries: &[String]) -> Option<std::path::PathBuf> {
+    #[cfg(target_os = "windows")]
+    let candidates: Vec<String> = if std::path::Path::new(name).extension().is_some() {
+        vec![name.to_string()]
+    } else {
+        env::var("PATHEXT")
+            .unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string())
+            .split(';')
+            .filter(|ext| !ext.is_empty())
+            .map(|ext| format!("{}{}", name, ext.to_lowercase()))
+            .collect()
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let candidates = vec![name.to_string()];
 
-    paths.join(path_separator)
+    path_entries.iter().find_map(|dir| {
+        candidates
+            .iter()
+            .map(|candidate| std::path::Path::new(dir).join(candidate))
+            .find(|path| is_executable(path))
+    })
 }
 
 // Backend state management
@@ -143,6 +541,26 @@ struct BackendState {
     port: u16,
     running: bool,
     pid: Option<u32>,  // Store PID for process tree cleanup on Windows
+    started_at: Option<Instant>,
+    restart_attempts: u32,  // Consecutive auto-restarts since the last manual start
+    restart_pending: bool,  // An auto-restart is waiting out its backoff delay
+    stopping: bool,  // stop_backend is in progress, so an exit is expected
+    prepared_token: Option<u64>,  // Issued by prepare_backend, consumed by commit_backend_start
+    capabilities: Option<serde_json::Value>,  // Fetched from the backend once it is ready
+    last_exit_code: Option<i32>,  // Of the previous backend process, if it exited with one
+    startup_timing: Option<StartupTiming>,  // Of the latest spawn, kept after it exits
+    startup: Option<Arc<StartupSignal>>,  // Readiness wait of the latest spawn
+    detachable: bool,  // Started with a listener that moves its output off our pipes on request
+    detached: Arc<tokio::sync::Notify>,  // Notified when the backend acknowledges detaching
+    forwarded_env: Option<Vec<String>>,  // Names of the variables we set on it; unknown when recovered
+}
+
+#[derive(Clone, Serialize)]
+pub struct StartupTiming {
+    pid: u32,
+    first_output_ms: Option<u64>,  // Spawn to the first stdout/stderr line
+    ready_ms: Option<u64>,         // Spawn to healthy
+    exited_without_output: bool,   // The process died before printing anything
 }
 
 impl Default for BackendState {
@@ -152,25 +570,74 @@ impl Default for BackendState {
             port: 8000,
             running: false,
             pid: None,
+            started_at: None,
+            restart_attempts: 0,
+            restart_pending: false,
+            stopping: false,
+            prepared_token: None,
+            capabilities: None,
+            last_exit_code: None,
+            startup_timing: None,
+            startup: None,
+            detachable: false,
+            detached: Arc::default(),
+            forwarded_env: None,
         }
     }
 }
 
-// Kill process tree on Windows using taskkill
+#[derive(Clone, Serialize)]
+struct CleanupLog {
+    level: String,  // "error", "warn", "info" or "debug"
+    message: String,
+}
+
+// Record what our kill/wait logic did in the app log and announce it as backend-cleanup-log,
+// so lifecycle actions are visible in packaged builds (where stdout goes nowhere)
+fn cleanup_log(app: &tauri::AppHandle, level: log::Level, message: String) {
+    log::log!(level, "{}", message);
+    let _ = app.emit("backend-cleanup-log", CleanupLog {
+        level: level.as_str().to_lowercase(),
+        message,
+    });
+}
+
+// Kill process tree on Windows using taskkill; `context` says why, e.g. "on exit"
 #[cfg(target_os = "windows")]
-fn kill_process_tree(pid: u32) {
+fn kill_process_tree(app: &tauri::AppHandle, pid: u32, context: &str) {
     // Use taskkill with /T flag to kill the entire process tree
     // /F = force, /T = tree (kill child processes), /PID = process ID
-    let _ = std::process::Command::new("taskkill")
+    let output = std::process::Command::new("taskkill")
         .args(["/F", "/T", "/PID", &pid.to_string()])
         .creation_flags(0x08000000) // CREATE_NO_WINDOW - hide the console window
         .output();
-    println!("Killed process tree for PID: {}", pid);
+    match output {
+        Ok(output) if output.status.success() => cleanup_log(
+            app,
+            log::Level::Info,
+            format!("Killed backend process tree (PID: {}) {}", pid, context),
+        ),
+        Ok(output) => cleanup_log(
+            app,
+            log::Level::Warn,
+            format!(
+                "taskkill failed for PID {} {}: {}",
+                pid,
+                context,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ),
+        Err(e) => cleanup_log(
+            app,
+            log::Level::Warn,
+            format!("Failed to run taskkill for PID {} {}: {}", pid, context, e),
+        ),
+    }
 }
 
 // On non-Windows, just use the standard kill
 #[cfg(not(target_os = "windows"))]
-fn kill_process_tree(_pid: u32) {
+fn kill_process_tree(_app: &tauri::AppHandle, _pid: u32, _context: &str) {
     // On Unix systems, the child.kill() should be sufficient
     // as we handle it in the main cleanup code
 }
@@ -184,176 +651,4972 @@ pub struct BackendStatus {
 }
 
 
-// Start the Python backend sidecar
-#[tauri::command]
-async fn start_backend(
-    app: tauri::AppHandle,
-    state: tauri::State<'_, SharedBackendState>,
-) -> Result<u16, String> {
-    // Check if already running (short lock)
-    {
-        let backend = state.lock().await;
-        if backend.running {
-            return Ok(backend.port);
-        }
-    }
+#[derive(Clone, Serialize)]
+struct RestartInfo {
+    attempt: u32,
+    max_attempts: u32,
+    delay_ms: u64,
+}
 
-    // Find an available port
-    let port = portpicker::pick_unused_port().unwrap_or(8000);
+// Auto-restart backoff: 1s, 2s, 4s, ... capped at 30s
+const RESTART_BASE_DELAY_MS: u64 = 1000;
+const RESTART_MAX_DELAY_MS: u64 = 30_000;
 
-    // Get enhanced PATH for the sidecar
-    let enhanced_path = get_enhanced_path();
+// A backend that stayed up at least this long before crashing starts a fresh backoff sequence
+const RESTART_STABLE_UPTIME: Duration = Duration::from_secs(60);
 
-    // Start the sidecar with enhanced environment
-    let sidecar = app
-        .shell()
-        .sidecar("python-backend")
-        .map_err(|e| format!("Failed to create sidecar command: {}", e))?
-        .args(["--port", &port.to_string()])
-        .env("PATH", enhanced_path);
+// Capped exponential backoff with jitter for the given (zero-based) restart attempt
+fn restart_delay(attempt: u32) -> Duration {
+    let delay = RESTART_BASE_DELAY_MS
+        .saturating_mul(1u64 << attempt.min(16))
+        .min(RESTART_MAX_DELAY_MS);
+    // Keep half of the delay fixed so jitter can never collapse it to zero
+    let half = delay / 2;
+    Duration::from_millis(half + fastrand::u64(0..=half))
+}
 
-    let (mut rx, child) = sidecar
-        .spawn()
-        .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
+// Schedule an automatic restart after an unexpected backend exit
+fn schedule_restart(app: tauri::AppHandle, state: SharedBackendState) {
+    tauri::async_runtime::spawn(async move {
+        let settings = app.state::<SharedSettings>().inner().clone();
+        let (auto_restart, max_attempts) = {
+            let settings = settings.lock().await;
+            (settings.auto_restart, settings.max_restart_attempts)
+        };
+        if !auto_restart {
+            return;
+        }
 
-    // Get PID for process tree cleanup on Windows
-    let pid = child.pid();
+        let attempt = {
+            let mut backend = state.lock().await;
+            if backend.restart_attempts >= max_attempts {
+                // Give up until the user starts the backend manually again
+                let _ = app.emit("backend-restart-exhausted", backend.restart_attempts);
+                let attempts = backend.restart_attempts;
+                drop(backend);
+                report_fatal(&app, format!("Backend kept crashing; gave up after {} restarts", attempts)).await;
+                return;
+            }
+            backend.restart_attempts += 1;
+            backend.restart_pending = true;
+            backend.restart_attempts
+        };
 
-    // Store the child process (short lock)
-    {
-        let mut backend = state.lock().await;
-        backend.child = Some(child);
-        backend.port = port;
-        backend.running = true;
-        backend.pid = Some(pid);
-    }
+        let delay = restart_delay(attempt - 1);
+        log::info!("Restarting backend in {}ms (attempt {}/{})", delay.as_millis(), attempt, max_attempts);
+        let _ = app.emit("backend-restarting", RestartInfo {
+            attempt,
+            max_attempts,
+            delay_ms: delay.as_millis() as u64,
+        });
+        tokio::time::sleep(delay).await;
 
-    // Spawn a task to handle sidecar output
-    let app_handle = app.clone();
-    let state_clone = state.inner().clone();
-    tauri::async_runtime::spawn(async move {
-        use tauri_plugin_shell::process::CommandEvent;
-        while let Some(event) = rx.recv().await {
-            match event {
-                CommandEvent::Stdout(line) => {
-                    let _ = app_handle.emit("backend-log", String::from_utf8_lossy(&line).to_string());
-                }
-                CommandEvent::Stderr(line) => {
-                    let _ = app_handle.emit("backend-error", String::from_utf8_lossy(&line).to_string());
-                }
-                CommandEvent::Terminated(payload) => {
-                    let _ = app_handle.emit("backend-terminated", payload.code);
-                    // Update state when backend terminates
-                    let mut backend = state_clone.lock().await;
-                    backend.running = false;
-                    backend.child = None;
-                    backend.pid = None;
-                    break;
-                }
-                _ => {}
+        // The user may have stopped or started the backend while we were waiting
+        {
+            let mut backend = state.lock().await;
+            if !backend.restart_pending || backend.running {
+                return;
             }
+            backend.restart_pending = false;
         }
-    });
 
-    // Wait a bit for the backend to start
-    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-
-    Ok(port)
+        if let Err(e) = spawn_backend(app.clone(), state.clone()).await {
+            let _ = app.emit("backend-error", format!("Auto-restart failed: {}", e));
+            // A failed spawn counts as another crash
+            schedule_restart(app, state);
+        }
+    });
 }
 
-// Stop the Python backend
-#[tauri::command]
-async fn stop_backend(state: tauri::State<'_, SharedBackendState>) -> Result<(), String> {
-    let mut backend = state.lock().await;
+// Number of recent output lines included in a crash report
+const CRASH_REPORT_LOG_LINES: usize = 200;
 
-    // On Windows, use taskkill to kill the entire process tree
-    #[cfg(target_os = "windows")]
-    let pid_to_wait = backend.pid;
+// Write a crash report for an abnormal backend exit (opt-in via settings)
+async fn capture_crash_report(
+    app: &tauri::AppHandle,
+    log_buffer: &SharedLogBuffer,
+    code: Option<i32>,
+    signal: Option<i32>,
+    uptime: Option<Duration>,
+) {
+    if !app.state::<SharedSettings>().lock().await.capture_crash_reports {
+        return;
+    }
 
-    #[cfg(target_os = "windows")]
-    if let Some(pid) = backend.pid {
-        kill_process_tree(pid);
+    let crash_dir = match app.path().app_log_dir() {
+        Ok(log_dir) => log_dir.join("crashes"),
+        Err(e) => {
+            log::warn!("Failed to resolve crash report dir: {}", e);
+            return;
+        }
+    };
+
+    let lines = log_buffer.lock().await.lines();
+    let mut report = format!(
+        "Owork backend crash report\n\
+         App version: {}\n\
+         Platform: {} ({})\n\
+         Timestamp: {}\n\
+         Exit code: {}\n\
+         Signal: {}\n\
+         Uptime: {}\n\
+         \n\
+         Last {} output lines:\n",
+        app.package_info().version,
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        logs::now_millis(),
+        code.map(|c| c.to_string()).unwrap_or_else(|| "none".to_string()),
+        signal.map(|s| s.to_string()).unwrap_or_else(|| "none".to_string()),
+        uptime.map(|u| format!("{:.1}s", u.as_secs_f64())).unwrap_or_else(|| "unknown".to_string()),
+        CRASH_REPORT_LOG_LINES,
+    );
+    for line in lines.iter().rev().take(CRASH_REPORT_LOG_LINES).rev() {
+        report.push_str(&format!("{} [{}] {}\n", line.timestamp, line.stream, line.line));
     }
 
-    if let Some(child) = backend.child.take() {
-        let _ = child.kill(); // Also try normal kill as fallback
+    match logs::write_crash_report(&crash_dir, &report) {
+        Ok(path) => {
+            let _ = app.emit("backend-crash-report", path.to_string_lossy().to_string());
+        }
+        Err(e) => log::warn!("Failed to write crash report: {}", e),
     }
+}
 
-    backend.running = false;
-    backend.pid = None;
+#[derive(Clone, Serialize)]
+struct StateReconciled {
+    pid: u32,
+    port: u16,
+    issue: String,  // "process_dead" or "port_unreachable"
+}
 
-    // Drop the lock before waiting
-    drop(backend);
+// How long a backend may take to start listening before the watchdog reports it unreachable
+const WATCHDOG_STARTUP_GRACE: Duration = Duration::from_secs(30);
 
-    // On Windows, wait for the process to fully exit to release file handles
-    // This is important for updates where the installer needs to overwrite the exe
-    #[cfg(target_os = "windows")]
-    if let Some(pid) = pid_to_wait {
-        wait_for_process_exit(pid).await;
+// Whether something accepts TCP connections on the given local port
+fn is_port_connectable(port: u16) -> bool {
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    std::net::TcpStream::connect_timeout(&addr, Duration::from_secs(1)).is_ok()
+}
+
+// Clear the state of a backend whose process is gone without us seeing it exit
+// Returns false if the state changed in the meantime and was left alone
+async fn reconcile_dead_backend(app: &tauri::AppHandle, state: &SharedBackendState, pid: u32, port: u16) -> bool {
+    let mut backend = state.lock().await;
+    if backend.pid != Some(pid) {
+        return false;
     }
+    backend.running = false;
+    backend.child = None;
+    backend.pid = None;
+    backend.started_at = None;
+    drop(backend);
 
-    Ok(())
+    let _ = app.emit("backend-state-reconciled", StateReconciled {
+        pid,
+        port,
+        issue: "process_dead".to_string(),
+    });
+    // Treat it like the crash whose Terminated event we missed
+    schedule_restart(app.clone(), state.clone());
+    true
 }
 
-// Wait for a process to exit on Windows
-#[cfg(target_os = "windows")]
-async fn wait_for_process_exit(pid: u32) {
-    use std::time::Duration;
+// Background task that periodically checks the tracked backend is really alive and listening
+// Self-heals a stuck `running: true` left behind by a missed Terminated event
+fn spawn_watchdog(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<SharedBackendState>().inner().clone();
+        let settings = app.state::<SharedSettings>().inner().clone();
 
-    // Try up to 10 times with 500ms delay (5 seconds total)
-    for i in 0..10 {
-        // Check if process still exists using tasklist
-        let output = std::process::Command::new("tasklist")
-            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
-            .creation_flags(0x08000000) // CREATE_NO_WINDOW
-            .output();
-
-        match output {
-            Ok(out) => {
-                let stdout = String::from_utf8_lossy(&out.stdout);
-                // tasklist with /FI "PID eq X" returns either:
-                // - A line with the process info if running
-                // - "INFO: No tasks are running..." if not running
-                // Check if output contains the executable name as a more reliable indicator
-                let process_running = stdout.contains("python-backend") ||
-                    (stdout.contains(&pid.to_string()) && !stdout.contains("INFO:"));
-                if !process_running {
-                    println!("Process {} has exited after {} checks", pid, i + 1);
-                    return;
+        loop {
+            let interval = settings.lock().await.watchdog_interval_secs;
+            let Some(interval) = interval.filter(|secs| *secs > 0) else {
+                // Disabled; check again later in case it gets enabled
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            };
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+
+            let (pid, port, started_at) = {
+                let backend = state.lock().await;
+                match (backend.running, backend.pid) {
+                    (true, Some(pid)) => (pid, backend.port, backend.started_at),
+                    _ => continue,
                 }
-            }
-            Err(_) => {
-                // If tasklist fails, assume process is gone
-                return;
+            };
+
+            let (alive, connectable) = tauri::async_runtime::spawn_blocking(move || {
+                let alive = process::is_process_alive(pid);
+                (alive, alive && is_port_connectable(port))
+            })
+            .await
+            .unwrap_or((true, true));
+
+            if !alive {
+                reconcile_dead_backend(&app, &state, pid, port).await;
+            } else if !connectable
+                && started_at.map(|t| t.elapsed() >= WATCHDOG_STARTUP_GRACE).unwrap_or(false)
+            {
+                let _ = app.emit("backend-state-reconciled", StateReconciled {
+                    pid,
+                    port,
+                    issue: "port_unreachable".to_string(),
+                });
             }
         }
+    });
+}
 
-        tokio::time::sleep(Duration::from_millis(500)).await;
-    }
+// The wake detector ticks every WAKE_TICK; a tick that takes WAKE_GAP longer than that in
+// wall-clock time means the machine was asleep (monotonic clocks stop during sleep on some
+// platforms, so the wall clock is what reveals the gap)
+const WAKE_TICK: Duration = Duration::from_secs(5);
+const WAKE_GAP: Duration = Duration::from_secs(30);
+
+// How long a backend gets to answer after wake; the network stack may need a moment
+const WAKE_HEALTH_GRACE: Duration = Duration::from_secs(10);
 
-    println!("Warning: Process {} may still be running after timeout", pid);
+#[derive(Clone, Serialize)]
+pub struct WakeCheck {
+    slept_secs: Option<u64>,  // None for a manual check
+    running: bool,            // Whether a backend was tracked as running
+    alive: bool,
+    healthy: bool,
+    action: &'static str,  // "none", "reconciled" (process was gone) or "restarted" (alive but unreachable)
 }
 
-// Get backend status
-#[tauri::command]
-async fn get_backend_status(state: tauri::State<'_, SharedBackendState>) -> Result<BackendStatus, String> {
-    let backend = state.lock().await;
+// Re-verify the backend after a sleep/wake cycle, reconciling or restarting it as needed,
+// and report the outcome as a backend-wake-check event
+async fn verify_backend_after_wake(app: &tauri::AppHandle, slept: Option<Duration>) -> WakeCheck {
+    let state = app.state::<SharedBackendState>().inner().clone();
+    let settings = app.state::<SharedSettings>().inner().clone();
+    let mut check = WakeCheck {
+        slept_secs: slept.map(|slept| slept.as_secs()),
+        running: false,
+        alive: false,
+        healthy: false,
+        action: "none",
+    };
+
+    let tracked = {
+        let backend = state.lock().await;
+        backend.pid.filter(|_| backend.running).map(|pid| (pid, backend.port))
+    };
+    if let Some((pid, port)) = tracked {
+        check.running = true;
+        check.alive = tauri::async_runtime::spawn_blocking(move || process::is_process_alive(pid))
+            .await
+            .unwrap_or(true);
+
+        if !check.alive {
+            if reconcile_dead_backend(app, &state, pid, port).await {
+                check.action = "reconciled";
+            }
+        } else {
+            let (url, expected_status, restart_on_wake) = {
+                let settings = settings.lock().await;
+                (
+                    settings.health_url(port),
+                    settings.health_expected_status.clone(),
+                    settings.restart_on_wake,
+                )
+            };
+            check.healthy = health::wait_until_ready(
+                &url,
+                expected_status.as_deref(),
+                health::HEALTH_TIMEOUT,
+                WAKE_HEALTH_GRACE,
+                || false,
+            )
+            .await
+            .is_ok();
+            if !check.healthy && restart_on_wake && restart(app).await.is_ok() {
+                check.action = "restarted";
+            }
+        }
+    }
+
+    let _ = app.emit("backend-wake-check", check.clone());
+    check
+}
+
+// Background task that notices system sleep/wake and re-verifies the backend afterwards
+fn spawn_wake_detector(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let before = std::time::SystemTime::now();
+            tokio::time::sleep(WAKE_TICK).await;
+            let elapsed = before.elapsed().unwrap_or_default();
+            if elapsed > WAKE_TICK + WAKE_GAP {
+                verify_backend_after_wake(&app, Some(elapsed - WAKE_TICK)).await;
+            }
+        }
+    });
+}
+
+// Run the post-wake verification on demand
+#[tauri::command]
+async fn check_backend_after_wake(app: tauri::AppHandle) -> Result<WakeCheck, String> {
+    Ok(verify_backend_after_wake(&app, None).await)
+}
+
+// Enable or disable (and persist) restarting a backend that is alive but unreachable after wake
+#[tauri::command]
+async fn set_restart_on_wake(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.restart_on_wake = enabled;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// External tools the backend can use, and the features that stop working without each
+const CAPABILITY_TOOLS: [(&str, &[&str]); 2] = [
+    ("node", &["agent", "mcp_servers"]),
+    ("git", &["plugins", "skill_marketplace"]),
+];
+
+#[derive(Clone, Serialize)]
+struct DegradedInfo {
+    missing_tools: Vec<String>,
+    disabled_features: Vec<String>,
+}
+
+// Which of the capability tools are on the sidecar PATH
+fn detect_capabilities(path_entries: &[String]) -> (Vec<String>, DegradedInfo) {
+    let mut available = Vec::new();
+    let mut degraded = DegradedInfo {
+        missing_tools: Vec::new(),
+        disabled_features: Vec::new(),
+    };
+
+    for (tool, features) in CAPABILITY_TOOLS {
+        if find_executable(tool, path_entries).is_some() {
+            available.push(tool.to_string());
+        } else {
+            degraded.missing_tools.push(tool.to_string());
+            degraded.disabled_features.extend(features.iter().map(|f| f.to_string()));
+        }
+    }
+
+    (available, degraded)
+}
+
+fn flush_log_batch(app: &tauri::AppHandle, event_stats: &logs::EventStats, batch: &logs::LogBatch) {
+    batch.flush(|lines| {
+        event_stats.record_coalesced(lines.len() as u64 - 1);
+        event_stats.record_emit(app.emit("backend-log-batch", lines).is_ok());
+    });
+}
+
+// Flush batched log lines as a single backend-log-batch event every `interval`
+// Runs until the batch is closed, then sends whatever is left
+fn spawn_log_batcher(
+    app: tauri::AppHandle,
+    event_stats: logs::SharedEventStats,
+    interval: Duration,
+) -> Arc<logs::LogBatch> {
+    let batch = Arc::new(logs::LogBatch::default());
+    let pending = batch.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            // Check before flushing so lines pushed just before closing still go out
+            let closed = pending.is_closed();
+            flush_log_batch(&app, &event_stats, &pending);
+            if closed {
+                break;
+            }
+        }
+    });
+    batch
+}
+
+// Stderr lines kept for the error returned when the backend exits during startup
+const STARTUP_STDERR_LINES: usize = 50;
+
+// Startup progress reported by the output task to spawn_backend's readiness wait
+#[derive(Default)]
+struct StartupSignal {
+    ready: std::sync::atomic::AtomicBool,     // Readiness sentinel seen
+    finished: std::sync::atomic::AtomicBool,  // The startup wait is over
+    exit: std::sync::Mutex<Option<Option<i32>>>,  // Exit code, once the process exited
+    stderr: std::sync::Mutex<Vec<String>>,
+    port_in_use: std::sync::atomic::AtomicBool,  // The backend reported it couldn't bind its port
+    done: tokio::sync::Notify,  // Notified once `finished` is set
+}
+
+impl StartupSignal {
+    fn finish(&self) {
+        self.finished.store(true, std::sync::atomic::Ordering::SeqCst);
+        self.done.notify_one();
+    }
+
+    async fn wait_finished(&self) {
+        while !self.finished.load(std::sync::atomic::Ordering::SeqCst) {
+            self.done.notified().await;
+        }
+    }
+
+    fn capture_stderr(&self, line: &str) {
+        if self.finished.load(std::sync::atomic::Ordering::SeqCst) {
+            return;
+        }
+        let mut stderr = self.stderr.lock().unwrap_or_else(|e| e.into_inner());
+        if stderr.len() >= STARTUP_STDERR_LINES {
+            stderr.remove(0);
+        }
+        stderr.push(line.to_string());
+    }
+
+    fn check_port_in_use(&self, line: &str) {
+        if !self.finished.load(std::sync::atomic::Ordering::SeqCst) && is_port_in_use_error(line) {
+            self.port_in_use.store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    // Record the exit, returning whether it happened while startup was still in progress
+    fn exited(&self, code: Option<i32>) -> bool {
+        *self.exit.lock().unwrap_or_else(|e| e.into_inner()) = Some(code);
+        !self.finished.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    fn exit_code(&self) -> Option<Option<i32>> {
+        *self.exit.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn stderr(&self) -> Vec<String> {
+        self.stderr.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+// Bind failures as printed by common server frameworks and runtimes
+// (uvicorn/asyncio, Node, Go, and Windows' WSAEADDRINUSE wording)
+fn is_port_in_use_error(line: &str) -> bool {
+    let line = line.to_ascii_lowercase();
+    line.contains("eaddrinuse")
+        || line.contains("error while attempting to bind")
+        || line.contains("address already in use")
+        || line.contains("only one usage of each socket address")
+}
+
+// Env var forcing the backend onto an exact port, for CI and scripted launches
+const PORT_ENV: &str = "OWORK_BACKEND_PORT";
+
+fn is_port_free(port: u16) -> bool {
+    std::net::TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+// Pick the backend port. Precedence: OWORK_BACKEND_PORT > preferred_port setting > a free port
+// The env override is strict (a busy port is an error), the preferred port is a best effort
+fn choose_port(preferred: Option<u16>) -> Result<u16, StartBackendError> {
+    if let Ok(value) = env::var(PORT_ENV) {
+        let port = value
+            .trim()
+            .parse::<u16>()
+            .ok()
+            .filter(|port| *port != 0)
+            .ok_or_else(|| format!("{} is not a valid port: {:?}", PORT_ENV, value))?;
+        if !is_port_free(port) {
+            return Err(StartBackendError::PortInUse {
+                port,
+                message: format!("Port {} (from {}) is already in use", port, PORT_ENV),
+            });
+        }
+        return Ok(port);
+    }
+
+    if let Some(port) = preferred.filter(|port| is_port_free(*port)) {
+        return Ok(port);
+    }
+
+    // Don't fall back to a fixed port: under port exhaustion it would most likely be busy too
+    portpicker::pick_unused_port().ok_or_else(|| "No free port available for the backend".to_string().into())
+}
+
+// Error returned by start_backend, typed so the UI can offer a fix for known failures
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StartBackendError {
+    PortInUse { port: u16, message: String },
+    // The interpreter or command wasn't found, so a fuller PATH could fix it
+    MissingRuntime { message: String },
+    Failed { message: String },
+}
+
+impl From<String> for StartBackendError {
+    fn from(message: String) -> Self {
+        StartBackendError::Failed { message }
+    }
+}
+
+impl std::fmt::Display for StartBackendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartBackendError::PortInUse { message, .. }
+            | StartBackendError::MissingRuntime { message }
+            | StartBackendError::Failed { message } => f.write_str(message),
+        }
+    }
+}
+
+// A line of output from a long setup step, for a progress screen
+#[derive(Clone, Serialize)]
+struct ProgressLine {
+    line: String,
+    percent: Option<u8>,  // Parsed from the line, e.g. "Downloading model... 45%"
+}
+
+// Last "<number>%" in a line of output, if any
+fn progress_percent(line: &str) -> Option<u8> {
+    line.match_indices('%').rev().find_map(|(index, _)| {
+        let digits: String = line[..index]
+            .chars()
+            .rev()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        let percent = digits.parse::<f64>().ok()?;
+        (0.0..=100.0).contains(&percent).then_some(percent as u8)
+    })
+}
+
+// Whether the backend still has to do its first-run initialization
+#[tauri::command]
+async fn is_first_run(app: tauri::AppHandle, settings: tauri::State<'_, SharedSettings>) -> Result<bool, String> {
+    let data_dir = settings.lock().await.backend_data_dir.clone();
+    Ok(!snapshot::is_backend_initialized(&app, data_dir.as_deref()))
+}
+
+// Line the backend prints to stdout once it is listening; a cooperative readiness signal
+// that can't be fooled by another process reusing the port
+const READY_SENTINEL: &str = "OWORK_BACKEND_READY";
+
+// Sidecar option enabling the detach listener, the stdin line asking it to detach, and the
+// stdout line acknowledging that its output now goes to its own log file instead of our pipes
+const DETACH_ARG: &str = "--detachable";
+const DETACH_COMMAND: &[u8] = b"detach\n";
+const DETACHED_SENTINEL: &str = "OWORK_BACKEND_DETACHED";
+const DETACH_WAIT: Duration = Duration::from_secs(2);
+
+// Report an error the backend can't recover from on its own via a backend-fatal event
+// With devtools_on_fatal enabled, also open devtools so support can debug release builds
+async fn report_fatal(app: &tauri::AppHandle, message: String) {
+    let _ = app.emit("backend-fatal", message);
+
+    let devtools_on_fatal = app.state::<SharedSettings>().lock().await.devtools_on_fatal;
+    if devtools_on_fatal {
+        if let Some(window) = app.get_webview_window("main") {
+            window.open_devtools();
+        }
+    }
+}
+
+// Retries for spawn failures caused by a busy sidecar binary (250ms, 500ms, 1s, 2s)
+const SPAWN_BUSY_RETRIES: u32 = 4;
+const SPAWN_BUSY_BASE_DELAY: Duration = Duration::from_millis(250);
+
+// Whether a spawn error means the binary is locked or still being written
+fn is_file_busy_error(error: &tauri_plugin_shell::Error) -> bool {
+    let tauri_plugin_shell::Error::Io(e) = error else {
+        return false;
+    };
+
+    // ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION
+    #[cfg(target_os = "windows")]
+    return matches!(e.raw_os_error(), Some(32) | Some(33));
+
+    #[cfg(unix)]
+    return e.raw_os_error() == Some(libc::ETXTBSY);
+}
+
+// Shells exit with 127 (POSIX) or 9009 (cmd.exe) for an unknown command
+fn is_missing_command_exit(code: Option<i32>) -> bool {
+    matches!(code, Some(127) | Some(9009))
+}
+
+// How long a start waits for the backend to fail fast (bad args, missing interpreter, port taken)
+// before returning its port; readiness after that is only reported through backend-ready
+const STARTUP_FAILURE_WINDOW: Duration = Duration::from_secs(3);
+
+// How long to wait for the output task to record a failed start's exit before retrying
+const RETRY_EXIT_WAIT: Duration = Duration::from_secs(2);
+
+// Spawn the sidecar and wire up its output handling
+// With the minimal PATH enabled, a start that fails for want of an interpreter or tool is
+// retried once with the full enhanced PATH, so a too-lean PATH can't leave the backend stuck
+// Boxed because the output task can schedule a restart that calls back into this function
+fn spawn_backend(
+    app: tauri::AppHandle,
+    state: SharedBackendState,
+) -> Pin<Box<dyn Future<Output = Result<u16, StartBackendError>> + Send>> {
+    Box::pin(async move {
+        let minimal_path = app.state::<SharedSettings>().lock().await.minimal_path;
+        match launch_backend(app.clone(), state.clone(), minimal_path).await {
+            Err(StartBackendError::MissingRuntime { message }) if minimal_path => {
+                log::warn!("Backend failed to start with the minimal PATH, retrying with the full PATH: {}", message);
+                let _ = app.emit(
+                    "backend-warning",
+                    format!(
+                        "The minimal PATH was insufficient for the backend; retrying with the full PATH ({})",
+                        message
+                    ),
+                );
+
+                // The failed process is forgotten by its output task once its exit is handled
+                let deadline = Instant::now() + RETRY_EXIT_WAIT;
+                while state.lock().await.running && Instant::now() < deadline {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                // Still recorded as running, a new start would just hand out the dead backend's port
+                if state.lock().await.running {
+                    report_fatal(&app, message.clone()).await;
+                    return Err(StartBackendError::MissingRuntime { message });
+                }
+                launch_backend(app, state, false).await
+            }
+            result => result,
+        }
+    })
+}
+
+fn launch_backend(
+    app: tauri::AppHandle,
+    state: SharedBackendState,
+    minimal_path: bool,
+) -> Pin<Box<dyn Future<Output = Result<u16, StartBackendError>> + Send>> {
+    Box::pin(async move {
+        // Check if already running (short lock)
+        {
+            let backend = state.lock().await;
+            if backend.running {
+                return Ok(backend.port);
+            }
+        }
+
+        let preferred_port = app.state::<SharedSettings>().lock().await.preferred_port;
+        let port = choose_port(preferred_port)?;
+
+        // Get enhanced PATH for the sidecar
+        let (mut path_entries, dropped) = sidecar_path_entries(minimal_path);
+        if is_path_effectively_empty(&path_entries) {
+            path_entries = default_system_path_dirs();
+            let _ = app.emit(
+                "backend-warning",
+                format!(
+                    "No usable PATH directories were found; falling back to {}",
+                    path_entries.join(PATH_SEPARATOR)
+                ),
+            );
+        }
+        if !dropped.is_empty() {
+            let _ = app.emit(
+                "backend-warning",
+                format!(
+                    "PATH is too long; dropped {} lowest-priority entries: {}",
+                    dropped.len(),
+                    dropped.join(PATH_SEPARATOR)
+                ),
+            );
+        }
+
+        let first_launch = !snapshot::has_launched_before(&app);
+        // The backend's first run against a data dir may download models/dependencies
+        let data_dir = app.state::<SharedSettings>().lock().await.backend_data_dir.clone();
+        let first_run = !snapshot::is_backend_initialized(&app, data_dir.as_deref());
+        let (max_line_length, log_batch_interval, strip_ansi, extra_args, resource_limits, backend_nice, readiness) = {
+            let settings = app.state::<SharedSettings>();
+            let settings = settings.lock().await;
+            (
+                settings.max_log_line_length,
+                settings.log_batch_interval_ms.map(Duration::from_millis),
+                settings.strip_ansi,
+                settings::sidecar_args(&settings),
+                settings.resource_limits(),
+                settings.backend_nice,
+                (
+                    settings.health_url(port),
+                    settings.health_expected_status.clone(),
+                    Duration::from_millis(settings.readiness_timeout_ms),
+                    settings.startup_timeout(first_launch, first_run),
+                ),
+            )
+        };
+        let log_buffer = app.state::<SharedLogBuffer>().inner().clone();
+
+        // A backend that needs the token will report its own auth errors, so only warn
+        let (backend_token, token_error) = app.state::<secrets::SharedTokenCache>().get();
+        if let Some(e) = token_error {
+            let _ = app.emit("backend-warning", e);
+        }
+        let env_vars = {
+            let settings = app.state::<SharedSettings>();
+            let settings = settings.lock().await;
+            backend_env(&path_entries, &settings, backend_token.as_deref())
+        };
+
+        let launch = {
+            let settings = app.state::<SharedSettings>();
+            let settings = settings.lock().await;
+            match settings.python_runtime {
+                settings::PythonRuntime::Bundled => BackendLaunch::Sidecar,
+                settings::PythonRuntime::System => BackendLaunch::System {
+                    python: resolve_system_python(settings.system_python_path.as_deref(), &path_entries)
+                        .map_err(|message| StartBackendError::MissingRuntime { message })?,
+                    source_dir: settings
+                        .backend_source_dir
+                        .clone()
+                        .ok_or_else(|| "No backend source dir configured for the system Python runtime".to_string())?,
+                    host: settings.health_host.clone(),
+                },
+                settings::PythonRuntime::External => {
+                    let (program, args) = resolve_external_command(
+                        &settings.external_command,
+                        &settings.health_host,
+                        port,
+                        &path_entries,
+                    )
+                    .map_err(|message| {
+                        if settings.external_command.is_empty() {
+                            StartBackendError::Failed { message }
+                        } else {
+                            StartBackendError::MissingRuntime { message }
+                        }
+                    })?;
+                    BackendLaunch::External { program, args }
+                }
+            }
+        };
+
+        // An emulated sidecar is slow and may not load native modules; point users to a native build
+        if let Some(emulation) = detect_emulation().filter(|info| info.emulated) {
+            let _ = app.emit(
+                "backend-warning",
+                format!(
+                    "Running a {} build under emulation on {}; performance will suffer. Install the native {} build if available.",
+                    emulation.process_arch, emulation.native_arch, emulation.native_arch
+                ),
+            );
+        }
+
+        // Tell the UI what won't work; backend_env tells the backend which tools it can rely on
+        let (_, degraded) = detect_capabilities(&path_entries);
+        if !degraded.missing_tools.is_empty() {
+            let _ = app.emit("backend-degraded", degraded);
+        }
+
+        // Only start the detach listener when it can be used. Finding out whether the sidecar has
+        // one means running it, so that happens in the background and this spawn goes without
+        let detachable = matches!(launch, BackendLaunch::Sidecar)
+            && app.state::<SharedSettings>().lock().await.keep_backend_alive_on_close
+            && match cached_detach_support(&app) {
+                Some(true) => true,
+                Some(false) => {
+                    let _ = app.emit(
+                        "backend-warning",
+                        "This backend can't keep running after the window closes; it will be stopped on close",
+                    );
+                    false
+                }
+                None => {
+                    probe_detach_support(&app);
+                    let _ = app.emit(
+                        "backend-warning",
+                        "Keeping the backend running after the window closes takes effect after the next backend restart",
+                    );
+                    false
+                }
+            };
+
+        // Start the sidecar with enhanced environment
+        let build_sidecar = || {
+            let command = match &launch {
+                // Run the sources with uvicorn directly; sidecar args are specific to the
+                // bundled entry point, so they aren't passed
+                BackendLaunch::System { python, source_dir, host } => app
+                    .shell()
+                    .command(python)
+                    .args(["-m", "uvicorn", "main:app", "--host", host, "--port", &port.to_string()])
+                    .current_dir(source_dir),
+                // The configured command carries its own args; it is still our child to manage
+                BackendLaunch::External { program, args } => app.shell().command(program).args(args),
+                BackendLaunch::Sidecar => app
+                    .shell()
+                    .sidecar("python-backend")
+                    .map_err(|e| format!("Failed to create sidecar command: {}", e))?
+                    .args(["--port", &port.to_string()])
+                    .args(detachable.then_some(DETACH_ARG))
+                    .args(&extra_args),
+            };
+            Ok::<_, String>(command.envs(env_vars.clone()))
+        };
+
+        // The backend inherits our fd limit, so raise it (for the whole app) before spawning
+        if app.state::<SharedSettings>().lock().await.raise_fd_limit {
+            if let Err(e) = limits::raise_fd_limit() {
+                let _ = app.emit("backend-warning", e);
+            }
+        }
+
+        // Set up the limits before spawning so the backend's whole process tree can be confined;
+        // a backend without its limits is still usable, so only warn
+        let confinement = limits::prepare(&resource_limits).unwrap_or_else(|e| {
+            let _ = app.emit("backend-warning", format!("Resource limits not applied: {}", e));
+            None
+        });
+
+        // Right after an update the new binary can still be locked or half-written,
+        // so retry those spawn failures a few times with backoff
+        let mut attempt = 0;
+        let (mut rx, child) = loop {
+            match build_sidecar()?.spawn() {
+                Ok(spawned) => break spawned,
+                Err(e) if is_file_busy_error(&e) && attempt < SPAWN_BUSY_RETRIES => {
+                    tokio::time::sleep(SPAWN_BUSY_BASE_DELAY * 2u32.pow(attempt)).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    let message = format!("Failed to spawn sidecar: {}", e);
+                    let not_found = matches!(&e, tauri_plugin_shell::Error::Io(e) if e.kind() == std::io::ErrorKind::NotFound);
+                    // spawn_backend retries these with the full PATH before giving up
+                    if !(minimal_path && not_found) {
+                        report_fatal(&app, message.clone()).await;
+                    }
+                    return Err(if not_found {
+                        StartBackendError::MissingRuntime { message }
+                    } else {
+                        StartBackendError::Failed { message }
+                    });
+                }
+            }
+        };
+
+        // Get PID for process tree cleanup on Windows
+        let pid = child.pid();
+        log::info!("Spawned backend (pid {}, port {})", pid, port);
+
+        // Listing the backend's descendants runs ps (a CIM query on Windows), so keep it off the
+        // runtime; the confinement comes back to live as long as the backend does
+        let confinement = match confinement {
+            Some(confinement) => {
+                let added = tauri::async_runtime::spawn_blocking(move || {
+                    let result = confinement.add_tree(pid);
+                    (confinement, result)
+                })
+                .await;
+                match added {
+                    Ok((confinement, result)) => {
+                        if let Err(e) = result {
+                            let _ = app.emit("backend-warning", format!("Resource limits not applied: {}", e));
+                        }
+                        Some(confinement)
+                    }
+                    Err(e) => {
+                        let _ = app.emit("backend-warning", format!("Resource limits not applied: {}", e));
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        if let Some(nice) = backend_nice {
+            if let Err(e) = apply_priority(pid, nice).await {
+                let _ = app.emit("backend-warning", format!("Priority not applied: {}", e));
+            }
+        }
+
+        // Store the child process (short lock)
+        let spawned_at = Instant::now();
+        let detached = {
+            let mut backend = state.lock().await;
+            backend.child = Some(child);
+            backend.port = port;
+            backend.running = true;
+            backend.pid = Some(pid);
+            backend.started_at = Some(spawned_at);
+            backend.capabilities = None;
+            backend.startup_timing = Some(StartupTiming {
+                pid,
+                first_output_ms: None,
+                ready_ms: None,
+                exited_without_output: false,
+            });
+            backend.detachable = detachable;
+            backend.forwarded_env = Some(env_vars.iter().map(|(name, _)| name.clone()).collect());
+            backend.detached.clone()
+        };
+
+        // Remember the backend on disk in case we exit without stopping it
+        let start_time = tauri::async_runtime::spawn_blocking(move || process::start_time(pid))
+            .await
+            .ok()
+            .flatten();
+        let _ = snapshot::save(&app, &snapshot::BackendSnapshot { pid, port, start_time });
+
+        // Lets the output task report the readiness sentinel or an early exit to the startup wait
+        let startup = Arc::new(StartupSignal::default());
+
+        // Spawn a task to handle sidecar output
+        let app_handle = app.clone();
+        let state_clone = state.clone();
+        let startup_signal = startup.clone();
+        let event_stats = app.state::<logs::SharedEventStats>().inner().clone();
+        let batch = log_batch_interval
+            .map(|interval| spawn_log_batcher(app.clone(), event_stats.clone(), interval));
+        let decode_stats = app.state::<logs::SharedDecodeStats>().inner().clone();
+        tauri::async_runtime::spawn(async move {
+            use tauri_plugin_shell::process::CommandEvent;
+            let mut stdout_decoder = logs::LineDecoder::redacting(backend_token.clone());
+            let mut stderr_decoder = logs::LineDecoder::redacting(backend_token.clone());
+            let mut seen_output = false;
+            while let Some(event) = rx.recv().await {
+                if !seen_output && matches!(event, CommandEvent::Stdout(_) | CommandEvent::Stderr(_)) {
+                    seen_output = true;
+                    let mut backend = state_clone.lock().await;
+                    if let Some(timing) = backend.startup_timing.as_mut().filter(|timing| timing.pid == pid) {
+                        timing.first_output_ms = Some(spawned_at.elapsed().as_millis() as u64);
+                    }
+                }
+                match event {
+                    CommandEvent::Stdout(line) => {
+                        let Some(mut line) = stdout_decoder.decode(&line, max_line_length, &decode_stats) else {
+                            continue;
+                        };
+                        if strip_ansi {
+                            line = logs::strip_ansi(&line);
+                        }
+                        if line.trim() == READY_SENTINEL {
+                            startup_signal.ready.store(true, std::sync::atomic::Ordering::SeqCst);
+                        }
+                        if line.trim() == DETACHED_SENTINEL {
+                            detached.notify_one();
+                        }
+                        startup_signal.check_port_in_use(&line);
+                        if first_run && !startup_signal.finished.load(std::sync::atomic::Ordering::SeqCst) {
+                            let _ = app_handle.emit("backend-init-progress", ProgressLine {
+                                percent: progress_percent(&line),
+                                line: line.clone(),
+                            });
+                        }
+                        let entry = logs::LogLine {
+                            stream: "stdout",
+                            line: line.clone(),
+                            timestamp: logs::now_millis(),
+                        };
+                        log_buffer.lock().await.push(entry.clone());
+                        match &batch {
+                            Some(batch) => batch.push(entry),
+                            None => event_stats.record_emit(app_handle.emit("backend-log", line).is_ok()),
+                        }
+                    }
+                    CommandEvent::Stderr(line) => {
+                        let Some(mut line) = stderr_decoder.decode(&line, max_line_length, &decode_stats) else {
+                            continue;
+                        };
+                        if strip_ansi {
+                            line = logs::strip_ansi(&line);
+                        }
+                        let entry = logs::LogLine {
+                            stream: "stderr",
+                            line: line.clone(),
+                            timestamp: logs::now_millis(),
+                        };
+                        startup_signal.capture_stderr(&line);
+                        startup_signal.check_port_in_use(&line);
+                        log_buffer.lock().await.push(entry.clone());
+                        match &batch {
+                            Some(batch) => batch.push(entry),
+                            None => event_stats.record_emit(app_handle.emit("backend-error", line).is_ok()),
+                        }
+                    }
+                    CommandEvent::Terminated(payload) => {
+                        // A last line cut off mid-character is still output
+                        for (stream, decoder, event) in [
+                            ("stdout", &mut stdout_decoder, "backend-log"),
+                            ("stderr", &mut stderr_decoder, "backend-error"),
+                        ] {
+                            let Some(mut line) = decoder.flush(max_line_length, &decode_stats) else {
+                                continue;
+                            };
+                            if strip_ansi {
+                                line = logs::strip_ansi(&line);
+                            }
+                            let entry = logs::LogLine {
+                                stream,
+                                line: line.clone(),
+                                timestamp: logs::now_millis(),
+                            };
+                            log_buffer.lock().await.push(entry.clone());
+                            match &batch {
+                                Some(batch) => batch.push(entry),
+                                None => event_stats.record_emit(app_handle.emit(event, line).is_ok()),
+                            }
+                        }
+
+                        // Send the last lines before announcing the exit; the flush waits for a timer
+                        // flush in progress, so no batch can arrive after backend-terminated
+                        if let Some(batch) = &batch {
+                            batch.close();
+                            flush_log_batch(&app_handle, &event_stats, batch);
+                        }
+                        let _ = app_handle.emit("backend-terminated", payload.code);
+                        let during_startup = startup_signal.exited(payload.code);
+
+                        // Update state when backend terminates, unless it was already
+                        // cleared by stop_backend or replaced by a newer process
+                        let mut backend = state_clone.lock().await;
+                        if let Some(timing) = backend.startup_timing.as_mut().filter(|timing| timing.pid == pid) {
+                            timing.exited_without_output = !seen_output;
+                        }
+                        if backend.pid != Some(pid) {
+                            break;
+                        }
+
+                        let expected = backend.stopping;
+                        let uptime = backend.started_at.map(|started| started.elapsed());
+                        let stable = uptime
+                            .map(|uptime| uptime >= RESTART_STABLE_UPTIME)
+                            .unwrap_or(false);
+                        if stable {
+                            backend.restart_attempts = 0;
+                        }
+
+                        backend.running = false;
+                        backend.child = None;
+                        backend.pid = None;
+                        backend.started_at = None;
+                        backend.last_exit_code = payload.code;
+                        drop(backend);
+
+                        // The process exited without stop_backend being called
+                        if !expected {
+                            if payload.code != Some(0) {
+                                capture_crash_report(&app_handle, &log_buffer, payload.code, payload.signal, uptime).await;
+                            }
+                            // A crash during startup is reported to the caller of start_backend
+                            // instead, since restarting would most likely fail the same way
+                            if !during_startup {
+                                schedule_restart(app_handle.clone(), state_clone.clone());
+                            }
+                        }
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Some(batch) = &batch {
+                batch.close();
+            }
+            // The backend has exited, so its cgroup can go
+            drop(confinement);
+        });
+
+        // Wait for the backend to print its readiness sentinel or answer its health endpoint in
+        // the background, reporting readiness through backend-ready; a slow start is only
+        // reported, not fatal. Failures within STARTUP_FAILURE_WINDOW are still returned, so the
+        // UI can offer another port and spawn_backend can retry with the full PATH
+        let (outcome_tx, outcome_rx) = tokio::sync::oneshot::channel();
+        state.lock().await.startup = Some(startup.clone());
+        {
+            let app = app.clone();
+            let state = state.clone();
+            tauri::async_runtime::spawn(async move {
+                let (url, expected_status, request_timeout, startup_timeout) = readiness;
+                let ready = health::wait_until_ready(
+                    &url,
+                    expected_status.as_deref(),
+                    request_timeout,
+                    startup_timeout,
+                    || {
+                        startup.ready.load(std::sync::atomic::Ordering::SeqCst)
+                            || startup.port_in_use.load(std::sync::atomic::Ordering::SeqCst)
+                            || startup.exit_code().is_some()
+                    },
+                )
+                .await;
+                startup.finish();
+
+                let outcome = if startup.port_in_use.load(std::sync::atomic::Ordering::SeqCst) {
+                    // Another process grabbed the port between picking and binding it: fail right
+                    // away instead of waiting out the startup timeout. Forget the process first so
+                    // its exit isn't treated as a crash
+                    let child = {
+                        let mut backend = state.lock().await;
+                        if backend.pid == Some(pid) {
+                            backend.running = false;
+                            backend.pid = None;
+                            backend.started_at = None;
+                            backend.child.take()
+                        } else {
+                            None
+                        }
+                    };
+                    if let Some(child) = child {
+                        let _ = child.kill();
+                    }
+                    snapshot::clear(&app);
+                    Err(StartBackendError::PortInUse {
+                        port,
+                        message: format!("Port {} is already in use by another process", port),
+                    })
+                } else if let Some(code) = startup.exit_code() {
+                    // The process died before becoming ready (bad args, missing interpreter, ...):
+                    // fail with what it printed rather than handing out the port of a dead backend
+                    let missing_command = is_missing_command_exit(code);
+                    let code = code.map(|code| code.to_string()).unwrap_or_else(|| "none".to_string());
+                    let stderr = startup.stderr();
+                    let message = if stderr.is_empty() {
+                        format!("Backend exited during startup (exit code {})", code)
+                    } else {
+                        format!("Backend exited during startup (exit code {}):\n{}", code, stderr.join("\n"))
+                    };
+                    Err(if missing_command {
+                        StartBackendError::MissingRuntime { message }
+                    } else {
+                        StartBackendError::Failed { message }
+                    })
+                } else {
+                    match ready {
+                        Ok(()) => {
+                            if first_launch {
+                                let _ = snapshot::mark_launched(&app);
+                            }
+                            if first_run {
+                                let _ = snapshot::mark_backend_initialized(&app, data_dir.as_deref());
+                            }
+                            if let Some(timing) =
+                                state.lock().await.startup_timing.as_mut().filter(|timing| timing.pid == pid)
+                            {
+                                timing.ready_ms = Some(spawned_at.elapsed().as_millis() as u64);
+                            }
+                            let _ = app.emit("backend-ready", port);
+                            tauri::async_runtime::spawn(refresh_capabilities(app.clone(), state.clone(), port));
+                        }
+                        Err(e) => {
+                            let _ = app.emit("backend-warning", e);
+                        }
+                    }
+                    Ok(())
+                };
+
+                // Nobody is waiting for a late failure any more, so report it instead
+                if let Err(Err(error)) = outcome_tx.send(outcome) {
+                    report_fatal(&app, error.to_string()).await;
+                }
+            });
+        }
+
+        match tokio::time::timeout(STARTUP_FAILURE_WINDOW, outcome_rx).await {
+            Ok(Ok(Err(error))) => {
+                // spawn_backend retries a missing runtime with the full PATH before giving up
+                match &error {
+                    StartBackendError::PortInUse { .. } => {}
+                    StartBackendError::MissingRuntime { .. } if minimal_path => {}
+                    _ => report_fatal(&app, error.to_string()).await,
+                }
+                Err(error)
+            }
+            _ => Ok(port),
+        }
+    })
+}
+
+// Start the Python backend sidecar
+// Returns the port once the process is up; readiness follows as a backend-ready event
+#[tauri::command]
+async fn start_backend(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedBackendState>,
+) -> Result<u16, StartBackendError> {
+    // A manual start resets the auto-restart backoff
+    {
+        let mut backend = state.lock().await;
+        backend.restart_attempts = 0;
+        backend.restart_pending = false;
+    }
+
+    spawn_backend(app, state.inner().clone()).await
+}
+
+#[derive(Clone, Serialize)]
+pub struct StartupLogLine {
+    stream: &'static str,  // "stdout" or "stderr"
+    line: String,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum StartupOutcome {
+    Ready { port: u16 },
+    Failed { error: String },
+    Timeout { port: u16 },  // Spawned, but not healthy within the startup timeout
+}
+
+// Start the backend, streaming its output through `on_log` until it is ready, fails or times out
+// Lets a setup screen show live startup output and the final outcome from a single call
+#[tauri::command]
+async fn start_backend_streaming(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedBackendState>,
+    on_log: tauri::ipc::Channel<StartupLogLine>,
+) -> Result<StartupOutcome, String> {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tauri::Listener;
+
+    {
+        let mut backend = state.lock().await;
+        if backend.running {
+            return Ok(StartupOutcome::Ready { port: backend.port });
+        }
+        backend.restart_attempts = 0;
+        backend.restart_pending = false;
+    }
+
+    // Piggyback on the events spawn_backend already emits
+    let ready = Arc::new(AtomicBool::new(false));
+    let terminated = Arc::new(AtomicBool::new(false));
+    let forward = |stream: &'static str| {
+        let on_log = on_log.clone();
+        move |event: tauri::Event| {
+            if let Ok(line) = serde_json::from_str::<String>(event.payload()) {
+                let _ = on_log.send(StartupLogLine { stream, line });
+            }
+        }
+    };
+    let listeners = [
+        app.listen("backend-log", forward("stdout")),
+        app.listen("backend-error", forward("stderr")),
+        app.listen("backend-log-batch", {
+            let on_log = on_log.clone();
+            move |event: tauri::Event| {
+                let Ok(lines) = serde_json::from_str::<Vec<serde_json::Value>>(event.payload()) else {
+                    return;
+                };
+                for entry in lines {
+                    let stream = if entry["stream"] == "stderr" { "stderr" } else { "stdout" };
+                    if let Some(line) = entry["line"].as_str() {
+                        let _ = on_log.send(StartupLogLine {
+                            stream,
+                            line: line.to_string(),
+                        });
+                    }
+                }
+            }
+        }),
+        app.listen("backend-ready", {
+            let ready = ready.clone();
+            move |_| ready.store(true, Ordering::SeqCst)
+        }),
+        app.listen("backend-terminated", {
+            let terminated = terminated.clone();
+            move |_| terminated.store(true, Ordering::SeqCst)
+        }),
+    ];
+
+    let result = spawn_backend(app.clone(), state.inner().clone()).await;
+    // spawn_backend returns before the backend is ready, so keep streaming until its startup wait ends
+    if result.is_ok() {
+        let startup = state.lock().await.startup.clone();
+        if let Some(startup) = startup {
+            startup.wait_finished().await;
+        }
+    }
+    for id in listeners {
+        app.unlisten(id);
+    }
+
+    Ok(match result {
+        Err(error) => StartupOutcome::Failed {
+            error: error.to_string(),
+        },
+        Ok(_) if terminated.load(Ordering::SeqCst) => StartupOutcome::Failed {
+            error: "Backend exited during startup".to_string(),
+        },
+        Ok(port) if ready.load(Ordering::SeqCst) => StartupOutcome::Ready { port },
+        Ok(port) => StartupOutcome::Timeout { port },
+    })
+}
+
+#[derive(Clone, Serialize)]
+pub struct PrerequisiteCheck {
+    name: String,
+    ok: bool,
+    required: bool,  // Optional tools only disable some features (see backend-degraded)
+    detail: String,  // Version/path on success, error message on failure
+}
+
+#[derive(Clone, Serialize)]
+struct PrerequisiteCheckEvent {
+    name: &'static str,
+    status: &'static str,  // "running", "ok" or "failed"
+    detail: Option<String>,
+}
+
+fn begin_check(app: &tauri::AppHandle, name: &'static str) {
+    let _ = app.emit("prerequisite-check", PrerequisiteCheckEvent {
+        name,
+        status: "running",
+        detail: None,
+    });
+}
+
+fn finish_check(
+    app: &tauri::AppHandle,
+    name: &'static str,
+    required: bool,
+    result: Result<String, String>,
+) -> PrerequisiteCheck {
+    let ok = result.is_ok();
+    let detail = result.unwrap_or_else(|e| e);
+    let _ = app.emit("prerequisite-check", PrerequisiteCheckEvent {
+        name,
+        status: if ok { "ok" } else { "failed" },
+        detail: Some(detail.clone()),
+    });
+    PrerequisiteCheck {
+        name: name.to_string(),
+        ok,
+        required,
+        detail,
+    }
+}
+
+// Detect the Git version on the enhanced PATH
+async fn git_version(timeout: Duration) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || detect_git_version(timeout))
+        .await
+        .map_err(|e| format!("Git check failed: {}", e))?
+}
+
+fn detect_git_version(timeout: Duration) -> Result<String, String> {
+    let git = find_executable("git", &get_enhanced_path_entries())
+        .ok_or_else(|| "Git is not installed or not in PATH".to_string())?;
+    let output = std::process::Command::new(&git)
+        .arg("--version")
+        .output_with_timeout(timeout)
+        .map_err(|e| format!("Failed to run {}: {}", git.display(), e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git --version failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[derive(Clone, Serialize)]
+pub struct CachedVersionCheck {
+    name: &'static str,
+    ok: bool,
+    detail: String,  // Version on success, error message on failure
+    checked_at: u64,  // Milliseconds since the Unix epoch
+}
+
+// Latest result of each Node/Python/Git version check, so diagnostics screens can render
+// without waiting for a (possibly slow, shell-fallback) check
+#[derive(Default)]
+pub struct VersionCache {
+    checks: std::sync::Mutex<Vec<CachedVersionCheck>>,
+}
+
+impl VersionCache {
+    fn store(&self, name: &'static str, result: &Result<String, String>) {
+        let check = CachedVersionCheck {
+            name,
+            ok: result.is_ok(),
+            detail: result.clone().unwrap_or_else(|e| e),
+            checked_at: logs::now_millis(),
+        };
+        let mut checks = self.checks.lock().unwrap_or_else(|e| e.into_inner());
+        checks.retain(|cached| cached.name != name);
+        checks.push(check);
+    }
+
+    fn snapshot(&self) -> Vec<CachedVersionCheck> {
+        self.checks.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+type SharedVersionCache = Arc<VersionCache>;
+
+// Run the prerequisite checks relevant to this platform, one at a time
+// Each check emits prerequisite-check events as it starts and finishes so the UI can
+// show progress instead of waiting for the slowest (shell fallback) check
+async fn run_prerequisite_checks(
+    app: &tauri::AppHandle,
+    login_shell_fallback: bool,
+    timeout: Duration,
+) -> Vec<PrerequisiteCheck> {
+    let mut checks = Vec::new();
+
+    let cache = app.state::<SharedVersionCache>();
+
+    begin_check(app, "node");
+    let node = nodejs_version(login_shell_fallback, timeout).await;
+    cache.store("node", &node);
+    checks.push(finish_check(app, "node", true, node));
+
+    begin_check(app, "python");
+    let python = python_version(login_shell_fallback, timeout).await;
+    cache.store("python", &python);
+    checks.push(finish_check(app, "python", true, python));
+
+    begin_check(app, "git");
+    let git = git_version(timeout).await;
+    cache.store("git", &git);
+    checks.push(finish_check(app, "git", false, git));
+
+    #[cfg(target_os = "windows")]
+    {
+        begin_check(app, "git_bash");
+        checks.push(finish_check(app, "git_bash", true, check_git_bash_path().await));
+    }
+
+    checks
+}
+
+// Run the Node/Python/Git version checks concurrently in the background and cache the results,
+// e.g. while the app is idle so diagnostics open instantly
+// Emits version-checks-warmed with the cached results when done
+#[tauri::command]
+async fn warm_version_checks(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+) -> Result<(), String> {
+    let (login_shell_fallback, timeout) = {
+        let settings = settings.lock().await;
+        (settings.login_shell_fallback, settings.version_check_timeout())
+    };
+
+    tauri::async_runtime::spawn(async move {
+        let node = tauri::async_runtime::spawn(nodejs_version(login_shell_fallback, timeout));
+        let python = tauri::async_runtime::spawn(python_version(login_shell_fallback, timeout));
+        let git = tauri::async_runtime::spawn(git_version(timeout));
+
+        let cache = app.state::<SharedVersionCache>();
+        for (name, check) in [("node", node), ("python", python), ("git", git)] {
+            if let Ok(result) = check.await {
+                cache.store(name, &result);
+            }
+        }
+        let _ = app.emit("version-checks-warmed", cache.snapshot());
+    });
+    Ok(())
+}
+
+// Cached version check results (empty until a check has run), each with when it ran
+#[tauri::command]
+async fn get_cached_version_checks(
+    cache: tauri::State<'_, SharedVersionCache>,
+) -> Result<Vec<CachedVersionCheck>, String> {
+    Ok(cache.snapshot())
+}
+
+// Run the prerequisite checks on their own, e.g. for an onboarding checklist
+#[tauri::command]
+async fn check_prerequisites(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+) -> Result<Vec<PrerequisiteCheck>, String> {
+    let (login_shell_fallback, timeout) = {
+        let settings = settings.lock().await;
+        (settings.login_shell_fallback, settings.version_check_timeout())
+    };
+    Ok(run_prerequisite_checks(&app, login_shell_fallback, timeout).await)
+}
+
+#[derive(Serialize)]
+pub struct PrepareReport {
+    token: u64,
+    path_entries: Vec<String>,
+    checks: Vec<PrerequisiteCheck>,
+    ready: bool,  // All required prerequisite checks passed
+}
+
+// Phase one of a two-phase start: compute the PATH and check prerequisites without spawning
+// The returned token must be passed to commit_backend_start to actually launch
+#[tauri::command]
+async fn prepare_backend(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+) -> Result<PrepareReport, String> {
+    let (login_shell_fallback, timeout) = {
+        let settings = settings.lock().await;
+        (settings.login_shell_fallback, settings.version_check_timeout())
+    };
+    let path_entries = get_enhanced_path_entries();
+    let checks = run_prerequisite_checks(&app, login_shell_fallback, timeout).await;
+    let ready = checks.iter().all(|check| check.ok || !check.required);
+
+    let token = fastrand::u64(..);
+    state.lock().await.prepared_token = Some(token);
+
+    Ok(PrepareReport {
+        token,
+        path_entries,
+        checks,
+        ready,
+    })
+}
+
+// Phase two: spawn the backend if the frontend approved the prepare_backend report
+#[tauri::command]
+async fn commit_backend_start(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedBackendState>,
+    token: u64,
+    approve: bool,
+) -> Result<Option<u16>, String> {
+    {
+        let mut backend = state.lock().await;
+        if backend.prepared_token.take() != Some(token) {
+            return Err("No matching prepare_backend call; prepare again before starting".to_string());
+        }
+        if !approve {
+            return Ok(None);
+        }
+        backend.restart_attempts = 0;
+        backend.restart_pending = false;
+    }
+
+    spawn_backend(app, state.inner().clone())
+        .await
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Clone, Serialize)]
+struct BackendStopped {
+    exited: bool,  // Whether the process is confirmed (or assumed, on Unix) to have exited
+}
+
+// Stop the Python backend
+#[tauri::command]
+async fn stop_backend(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+) -> Result<(), String> {
+    shutdown_backend(&app, state.inner(), settings.inner()).await;
+    Ok(())
+}
+
+// Gracefully stop the backend (shutdown endpoint first, then kill), emitting backend-stopped
+async fn shutdown_backend(app: &tauri::AppHandle, state: &SharedBackendState, settings: &SharedSettings) {
+    let target = {
+        let mut backend = state.lock().await;
+        if backend.running {
+            backend.stopping = true;
+            log::info!("Stopping backend (pid {:?})", backend.pid);
+
+            // Let the UI show a "shutting down" state during the (up to 5s) wait below
+            let _ = app.emit("backend-stopping", backend.pid);
+        }
+
+        backend.pid.filter(|_| backend.running).map(|pid| (pid, backend.port))
+    };
+
+    // Give the backend a chance to clean up through its shutdown endpoint before killing it
+    let (shutdown_url, shutdown_timeout) = {
+        let settings = settings.lock().await;
+        let url = match (&settings.shutdown_path, target) {
+            (Some(path), Some((pid, port))) => Some((pid, settings.endpoint_url(port, path))),
+            _ => None,
+        };
+        (url, Duration::from_millis(settings.shutdown_timeout_ms))
+    };
+    if let Some((pid, url)) = shutdown_url {
+        match health::request_shutdown(&url, shutdown_timeout).await {
+            Ok(()) => {
+                let exited = wait_for_backend_exit(state, pid, shutdown_timeout).await;
+                log::debug!("Graceful shutdown of pid {} requested; exited in time: {}", pid, exited);
+            }
+            Err(e) => log::debug!("Graceful shutdown of pid {} failed: {}", pid, e),
+        }
+    }
+
+    let mut backend = state.lock().await;
+
+    // On Windows, use taskkill to kill the entire process tree
+    #[cfg(target_os = "windows")]
+    let pid_to_wait = backend.pid;
+
+    #[cfg(target_os = "windows")]
+    if let Some(pid) = backend.pid {
+        kill_process_tree(app, pid, "on stop");
+    }
+
+    let mut exited = true;
+    if let Some(child) = backend.child.take() {
+        exited = child.kill().is_ok(); // Also try normal kill as fallback
+    } else if let Some(pid) = backend.pid.filter(|_| backend.running) {
+        // A recovered backend has no child handle
+        exited = process::kill(pid) || !process::is_process_alive(pid);
+    }
+
+    backend.running = false;
+    backend.pid = None;
+    backend.started_at = None;
+    backend.restart_pending = false;
+    backend.stopping = false;
+
+    // Drop the lock before waiting
+    drop(backend);
+
+    // On Windows, wait for the process to fully exit to release file handles
+    // This is important for updates where the installer needs to overwrite the exe
+    #[cfg(target_os = "windows")]
+    if let Some(pid) = pid_to_wait {
+        exited = wait_for_process_exit(app, pid).await;
+    }
+
+    if exited {
+        snapshot::clear(app);
+    } else {
+        log::warn!("Backend may still be running after stop");
+    }
+
+    let _ = app.emit("backend-stopped", BackendStopped { exited });
+}
+
+#[derive(Serialize)]
+pub struct NukeSummary {
+    stopped_pid: Option<u32>,  // The tracked backend, force-killed
+    killed_orphans: Vec<u32>,  // Other sidecar processes left behind by earlier runs
+    log_bytes_freed: u64,
+    settings_deleted: bool,
+}
+
+// Support's last resort: force-stop every backend and wipe our persisted state
+// (settings, pid/port snapshot, launch marker, logs), leaving a clean slate for a reinstall
+// The backend's own data dir is left alone
+#[tauri::command]
+async fn nuke_backend(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+    log_buffer: tauri::State<'_, SharedLogBuffer>,
+) -> Result<NukeSummary, String> {
+    // No graceful shutdown: the backend may be the thing that is wedged
+    let stopped_pid = {
+        let mut backend = state.lock().await;
+        let pid = backend.pid;
+
+        #[cfg(target_os = "windows")]
+        if let Some(pid) = pid {
+            kill_process_tree(&app, pid, "on reset");
+        }
+        match backend.child.take() {
+            Some(child) => {
+                let _ = child.kill();
+            }
+            None => {
+                if let Some(pid) = pid {
+                    process::kill(pid);
+                }
+            }
+        }
+
+        // Also drops restart bookkeeping, so the exit isn't followed by an auto-restart
+        *backend = BackendState::default();
+        pid
+    };
+
+    let killed_orphans = tauri::async_runtime::spawn_blocking(move || {
+        let own_pid = std::process::id();
+        process::list_processes()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| p.name.contains("python-backend") && p.pid != own_pid && Some(p.pid) != stopped_pid)
+            .filter(|p| process::kill(p.pid))
+            .map(|p| p.pid)
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| format!("Failed to kill orphaned backends: {}", e))?;
+
+    snapshot::clear(&app);
+    snapshot::clear_launched(&app);
+    let settings_deleted = settings::delete(&app)?;
+    let defaults = settings::BackendSettings::default();
+
+    // Undo what the old settings applied to the running app, as replace_settings would
+    apply_log_level(defaults.verbose_logging);
+    app.state::<SharedCommandLimiter>()
+        .lock()
+        .await
+        .set_limit(defaults.max_concurrent_backend_commands);
+    *settings.lock().await = defaults;
+
+    let log_bytes_freed = {
+        let mut log_buffer = log_buffer.lock().await;
+        log_buffer.clear();
+        log_buffer.set_capacity(logs::DEFAULT_LOG_BUFFER_CAPACITY);
+        log_buffer.clear_files()
+    };
+
+    Ok(NukeSummary {
+        stopped_pid,
+        killed_orphans,
+        log_bytes_freed,
+        settings_deleted,
+    })
+}
+
+// Default and maximum time the frontend gets to acknowledge a shutdown
+const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(3);
+const MAX_SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+// Lets the frontend persist state before the backend is killed on close/exit
+#[derive(Default)]
+pub struct ShutdownHooks {
+    state: std::sync::Mutex<ShutdownHookState>,
+    acknowledged: tokio::sync::Notify,
+}
+
+#[derive(Default)]
+struct ShutdownHookState {
+    grace: Option<Duration>,  // Set while a frontend hook is registered
+    started: bool,            // The grace window was already given; don't delay again
+}
+
+impl ShutdownHooks {
+    // Start the grace window if a hook is registered, returning its length
+    fn begin(&self) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.started {
+            return None;
+        }
+        let grace = state.grace?;
+        state.started = true;
+        Some(grace)
+    }
+}
+
+type SharedShutdownHooks = Arc<ShutdownHooks>;
+
+// Ask the frontend to get ready (app-shutdown-requested) and wait for its acknowledgment,
+// never longer than the grace window so a stuck frontend can't block quitting
+async fn wait_for_shutdown_ack(app: &tauri::AppHandle, grace: Duration) {
+    let hooks = app.state::<SharedShutdownHooks>().inner().clone();
+    let _ = app.emit("app-shutdown-requested", grace.as_millis() as u64);
+    let _ = tokio::time::timeout(grace, hooks.acknowledged.notified()).await;
+}
+
+// Register the frontend for an app-shutdown-requested event before the backend is killed
+// on close/exit; it must call acknowledge_shutdown within grace_ms (default 3s, max 10s)
+#[tauri::command]
+async fn register_shutdown_hook(
+    hooks: tauri::State<'_, SharedShutdownHooks>,
+    grace_ms: Option<u64>,
+) -> Result<(), String> {
+    let grace = grace_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_SHUTDOWN_GRACE)
+        .min(MAX_SHUTDOWN_GRACE);
+    hooks.state.lock().unwrap_or_else(|e| e.into_inner()).grace = Some(grace);
+    Ok(())
+}
+
+#[tauri::command]
+async fn unregister_shutdown_hook(hooks: tauri::State<'_, SharedShutdownHooks>) -> Result<(), String> {
+    hooks.state.lock().unwrap_or_else(|e| e.into_inner()).grace = None;
+    Ok(())
+}
+
+// The frontend is ready for the backend to be killed
+#[tauri::command]
+async fn acknowledge_shutdown(hooks: tauri::State<'_, SharedShutdownHooks>) -> Result<(), String> {
+    if hooks.state.lock().unwrap_or_else(|e| e.into_inner()).started {
+        hooks.acknowledged.notify_one();
+    }
+    Ok(())
+}
+
+// Restart the backend: a graceful stop followed by a fresh spawn
+#[tauri::command]
+async fn restart_backend(app: tauri::AppHandle) -> Result<u16, String> {
+    restart(&app).await
+}
+
+async fn restart(app: &tauri::AppHandle) -> Result<u16, String> {
+    let state = app.state::<SharedBackendState>().inner().clone();
+    let settings = app.state::<SharedSettings>().inner().clone();
+
+    shutdown_backend(app, &state, &settings).await;
+    {
+        let mut backend = state.lock().await;
+        backend.restart_attempts = 0;
+        backend.restart_pending = false;
+    }
+    spawn_backend(app.clone(), state).await.map_err(|e| e.to_string())
+}
+
+// Wait until the output task has observed the backend process exiting
+// Returns false if it is still tracked as running after the timeout
+async fn wait_for_backend_exit(state: &SharedBackendState, pid: u32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if state.lock().await.pid != Some(pid) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+// Wait for a process to exit on Windows
+// Returns false if it may still be running after the timeout
+#[cfg(target_os = "windows")]
+async fn wait_for_process_exit(app: &tauri::AppHandle, pid: u32) -> bool {
+    use std::time::Duration;
+
+    // Try up to 10 times with 500ms delay (5 seconds total)
+    for i in 0..10 {
+        // Check if process still exists using tasklist
+        match process::tasklist_image_name(pid) {
+            Ok(Some(image_name)) => {
+                cleanup_log(
+                    app,
+                    log::Level::Debug,
+                    format!("Process {} ({}) is still running", pid, image_name),
+                );
+            }
+            Ok(None) => {
+                cleanup_log(
+                    app,
+                    log::Level::Info,
+                    format!("Process {} has exited after {} checks", pid, i + 1),
+                );
+                return true;
+            }
+            Err(e) => {
+                // If tasklist fails, assume process is gone
+                cleanup_log(
+                    app,
+                    log::Level::Warn,
+                    format!("Assuming process {} exited; tasklist failed: {}", pid, e),
+                );
+                return true;
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    cleanup_log(
+        app,
+        log::Level::Warn,
+        format!("Process {} may still be running after timeout", pid),
+    );
+    false
+}
+
+// Get backend status
+#[tauri::command]
+async fn get_backend_status(state: tauri::State<'_, SharedBackendState>) -> Result<BackendStatus, String> {
+    let backend = state.lock().await;
     Ok(BackendStatus {
         running: backend.running,
         port: backend.port,
     })
 }
 
-// Get backend port
+#[derive(Serialize)]
+pub struct DetailedBackendStatus {
+    running: bool,
+    port: u16,
+    pid: Option<u32>,
+    uptime_secs: Option<u64>,
+    last_exit_code: Option<i32>,
+    last_error: Option<String>,  // Most recent line logged at ERROR or above
+    healthy: Option<bool>,       // None when not running
+    health_url: String,
+}
+
+// Everything a backend status panel needs in one call
+#[tauri::command]
+async fn get_detailed_status(
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+    log_buffer: tauri::State<'_, SharedLogBuffer>,
+) -> Result<DetailedBackendStatus, String> {
+    let (running, port, pid, uptime_secs, last_exit_code) = {
+        let backend = state.lock().await;
+        (
+            backend.running,
+            backend.port,
+            backend.pid,
+            backend.started_at.map(|started| started.elapsed().as_secs()),
+            backend.last_exit_code,
+        )
+    };
+    let (health_url, expected_status) = {
+        let settings = settings.lock().await;
+        (settings.health_url(port), settings.health_expected_status.clone())
+    };
+    let last_error = log_buffer.lock().await.last_line_at_level(logs::ERROR_LEVEL);
+
+    let healthy = if running {
+        Some(
+            health::check_health(&health_url, expected_status.as_deref(), health::HEALTH_TIMEOUT)
+                .await
+                .unwrap_or(false),
+        )
+    } else {
+        None
+    };
+
+    Ok(DetailedBackendStatus {
+        running,
+        port,
+        pid,
+        uptime_secs,
+        last_exit_code,
+        last_error,
+        healthy,
+        health_url,
+    })
+}
+
+// Get backend port
+#[tauri::command]
+async fn get_backend_port(state: tauri::State<'_, SharedBackendState>) -> Result<u16, String> {
+    let backend = state.lock().await;
+    Ok(backend.port)
+}
+
+// Get recent backend output from the in-memory ring buffer
+#[tauri::command]
+async fn get_backend_logs(log_buffer: tauri::State<'_, SharedLogBuffer>) -> Result<Vec<logs::LogLine>, String> {
+    Ok(log_buffer.lock().await.lines())
+}
+
+// Just the last `lines` stderr lines (default 50), for a quick look at recent warnings
+// without pulling the whole combined log
+#[tauri::command]
+async fn get_recent_stderr(
+    log_buffer: tauri::State<'_, SharedLogBuffer>,
+    lines: Option<usize>,
+) -> Result<Vec<logs::LogLine>, String> {
+    Ok(log_buffer.lock().await.recent("stderr", lines.unwrap_or(50)))
+}
+
+// Backend output at or above min_level ("debug", "info", "warning", "error", "critical")
+// within [since, until] (epoch ms), for attaching a focused excerpt to a bug report
+// Reads the persisted log files when there are any, since they reach further back than the buffer
+#[tauri::command]
+async fn export_filtered_backend_logs(
+    log_buffer: tauri::State<'_, SharedLogBuffer>,
+    min_level: Option<String>,
+    since: Option<u64>,
+    until: Option<u64>,
+) -> Result<Vec<logs::LeveledLogLine>, String> {
+    let min_level = min_level
+        .map(|level| logs::level_rank(&level).ok_or_else(|| format!("Unknown log level: {}", level)))
+        .transpose()?;
+    // Only hold the buffer lock to find the files; reading them can take a while
+    let files = log_buffer.lock().await.persisted_files();
+    let lines = match files {
+        Some(files) => tauri::async_runtime::spawn_blocking(move || logs::read_persisted_lines(&files, since, until))
+            .await
+            .map_err(|e| format!("Failed to read backend logs: {}", e))?,
+        None => log_buffer.lock().await.lines(),
+    };
+    Ok(logs::filter_lines(lines, min_level, since, until))
+}
+
+// Clear the in-memory log buffer and the persisted log files, returning the bytes freed
+// Safe to call while the backend is running
+#[tauri::command]
+async fn clear_backend_logs(log_buffer: tauri::State<'_, SharedLogBuffer>) -> Result<u64, String> {
+    let mut log_buffer = log_buffer.lock().await;
+    log_buffer.clear();
+    Ok(log_buffer.clear_files())
+}
+
+// Get all persisted backend settings as one object
+#[tauri::command]
+async fn get_backend_settings(
+    settings: tauri::State<'_, SharedSettings>,
+) -> Result<settings::BackendSettings, String> {
+    Ok(settings.lock().await.clone())
+}
+
+// Validate, persist and apply a complete settings object
+// Returns whether the running backend needs a restart for the changes to take effect
+async fn replace_settings(app: &tauri::AppHandle, updated: settings::BackendSettings) -> Result<bool, String> {
+    updated.validate()?;
+
+    let (running, pid) = {
+        let backend = app.state::<SharedBackendState>();
+        let backend = backend.lock().await;
+        (backend.running, backend.pid)
+    };
+    let settings = app.state::<SharedSettings>().inner().clone();
+    let mut settings = settings.lock().await;
+
+    // Only check directories that changed; validating a data dir creates it
+    let data_dir = updated
+        .backend_data_dir
+        .clone()
+        .filter(|dir| settings.backend_data_dir.as_ref() != Some(dir));
+    let source_dir = updated
+        .backend_source_dir
+        .clone()
+        .filter(|_| updated.python_runtime == settings::PythonRuntime::System);
+    let system_python = updated
+        .system_python_path
+        .clone()
+        .filter(|_| updated.python_runtime == settings::PythonRuntime::System);
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(dir) = data_dir {
+            settings::validate_data_dir(&dir)?;
+        }
+        if let Some(dir) = source_dir {
+            if !std::path::Path::new(&dir).join("main.py").is_file() {
+                return Err(format!("{} does not contain the backend's main.py", dir));
+            }
+        }
+        if let Some(python) = system_python {
+            if !is_executable(std::path::Path::new(&python)) {
+                return Err(format!("{} is not an executable file", python));
+            }
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to validate settings: {}", e))??;
+
+    // Apply the priority before saving, so a priority we can't set isn't persisted either;
+    // clearing it leaves the running backend's priority as is
+    if let (Some(pid), Some(nice)) = (pid.filter(|_| running), updated.backend_nice) {
+        if updated.backend_nice != settings.backend_nice {
+            apply_priority(pid, nice).await?;
+        }
+    }
+
+    settings::save(app, &updated)?;
+    let requires_restart = settings.requires_restart(&updated);
+
+    // Settings the individual setters apply right away
+    app.state::<SharedLogBuffer>()
+        .lock()
+        .await
+        .set_capacity(updated.log_buffer_capacity);
+    if updated.max_concurrent_backend_commands != settings.max_concurrent_backend_commands {
+        let limit = updated.max_concurrent_backend_commands;
+        let limiter = app.state::<SharedCommandLimiter>();
+        limiter.lock().await.set_limit(limit);
+    }
+    apply_log_level(updated.verbose_logging);
+    *settings = updated;
+    Ok(running && requires_restart)
+}
+
+// Replace all persisted backend settings at once, after validating the whole object
+// The object must contain every setting (as returned by get_backend_settings) and no others
+// Returns true if the running backend must be restarted for the changes to take effect
+#[tauri::command]
+async fn set_backend_settings(
+    app: tauri::AppHandle,
+    settings: serde_json::Value,
+) -> Result<bool, String> {
+    replace_settings(&app, settings::from_complete_json(settings)?).await
+}
+
+// Restore every backend setting to its default
+// Returns true if the running backend must be restarted for the changes to take effect
+#[tauri::command]
+async fn reset_backend_settings_to_default(app: tauri::AppHandle) -> Result<bool, String> {
+    replace_settings(&app, settings::BackendSettings::default()).await
+}
+
+// Set (and persist) log batching: with an interval, output is sent as backend-log-batch
+// events at most every interval_ms instead of one event per line (None = per-line events)
+// Applies to backends started after the change
+#[tauri::command]
+async fn set_log_batching(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    interval_ms: Option<u64>,
+) -> Result<(), String> {
+    if let Some(interval_ms) = interval_ms {
+        if !(10..=5000).contains(&interval_ms) {
+            return Err(format!("Batch interval must be between 10 and 5000 ms: {}", interval_ms));
+        }
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.log_batch_interval_ms = interval_ms;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Set (and persist) whether ANSI escape codes are stripped from backend output
+// Disable to render colors in the UI; applies to backends started after the change
+#[tauri::command]
+async fn set_strip_ansi(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.strip_ansi = enabled;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Start a fresh backend.log now, returning the path of the segment that was just closed
+// Handy for capturing exactly the output of a single reproduction
+#[tauri::command]
+async fn rotate_backend_log(log_buffer: tauri::State<'_, SharedLogBuffer>) -> Result<String, String> {
+    let rotated = log_buffer.lock().await.rotate()?;
+    Ok(rotated.display().to_string())
+}
+
+// Log event throughput over the IPC bridge since launch (rate, peak, dropped/coalesced counts)
+#[tauri::command]
+async fn get_ipc_stats(
+    event_stats: tauri::State<'_, logs::SharedEventStats>,
+) -> Result<logs::EventStatsSnapshot, String> {
+    Ok(event_stats.snapshot())
+}
+
+// Report how much backend output needed line-ending or encoding fixes, e.g. CRLF output
+// or non-UTF-8 bytes from a Windows backend
+#[tauri::command]
+async fn get_output_decoding_stats(
+    decode_stats: tauri::State<'_, logs::SharedDecodeStats>,
+) -> Result<logs::DecodeStatsSnapshot, String> {
+    Ok(decode_stats.snapshot())
+}
+
+// Set (and persist) the ring-buffer capacity and the per-line length cap (0 = unlimited)
+// The line cap applies to backends started after the change
+#[tauri::command]
+async fn set_log_limits(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    log_buffer: tauri::State<'_, SharedLogBuffer>,
+    buffer_capacity: Option<usize>,
+    max_line_length: Option<usize>,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    if let Some(buffer_capacity) = buffer_capacity {
+        updated.log_buffer_capacity = buffer_capacity;
+    }
+    if let Some(max_line_length) = max_line_length {
+        updated.max_log_line_length = max_line_length;
+    }
+    settings::validate_log_limits(updated.log_buffer_capacity, updated.max_log_line_length)?;
+    settings::save(&app, &updated)?;
+
+    log_buffer.lock().await.set_capacity(updated.log_buffer_capacity);
+    *settings = updated;
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct AppVersion {
+    version: String,
+    commit: Option<String>,
+    build_date: Option<String>,
+}
+
+// Get the app version plus the git commit and build date embedded by build.rs
+#[tauri::command]
+async fn get_app_version(app: tauri::AppHandle) -> Result<AppVersion, String> {
+    let embedded = |value: Option<&str>| value.filter(|v| !v.is_empty()).map(|v| v.to_string());
+
+    Ok(AppVersion {
+        version: app.package_info().version.to_string(),
+        commit: embedded(option_env!("OWORK_GIT_COMMIT")),
+        build_date: embedded(option_env!("OWORK_BUILD_DATE")),
+    })
+}
+
+// Get the virtualenv/conda env detected at launch and forwarded to the sidecar
+#[tauri::command]
+async fn get_python_env() -> Result<PythonEnv, String> {
+    Ok(detect_python_env())
+}
+
+#[derive(Serialize)]
+pub struct PythonInterpreter {
+    path: String,
+    version: String,
+}
+
+// Timeout for probing a single interpreter with --version
+const VERSION_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn subdirs(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+// Every Python executable we can find: on the enhanced PATH, in pyenv versions,
+// conda base/envs and (on macOS) versioned Homebrew kegs
+fn python_candidates() -> Vec<std::path::PathBuf> {
+    use std::path::{Path, PathBuf};
+
+    let mut candidates: Vec<PathBuf> = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    let names: Vec<String> = vec!["python.exe".to_string(), "python3.exe".to_string()];
+
+    #[cfg(not(target_os = "windows"))]
+    let names: Vec<String> = ["python3", "python"]
+        .iter()
+        .map(|name| name.to_string())
+        .chain((8..=14).map(|minor| format!("python3.{}", minor)))
+        .collect();
+
+    for dir in get_enhanced_path_entries() {
+        for name in &names {
+            candidates.push(Path::new(&dir).join(name));
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let home = env::var("HOME").unwrap_or_default();
+        let home = Path::new(&home);
+
+        for version in subdirs(&home.join(".pyenv/versions")) {
+            candidates.push(version.join("bin/python3"));
+        }
+
+        let conda_roots = [
+            home.join("miniconda3"),
+            home.join("anaconda3"),
+            home.join("miniforge3"),
+            home.join("mambaforge"),
+            PathBuf::from("/opt/miniconda3"),
+            PathBuf::from("/opt/anaconda3"),
+        ];
+        for root in conda_roots {
+            candidates.push(root.join("bin/python3"));
+            for conda_env in subdirs(&root.join("envs")) {
+                candidates.push(conda_env.join("bin/python3"));
+            }
+        }
+
+        for opt in ["/opt/homebrew/opt", "/usr/local/opt"] {
+            for keg in subdirs(Path::new(opt)) {
+                let is_python = keg
+                    .file_name()
+                    .map(|name| name.to_string_lossy().starts_with("python@"))
+                    .unwrap_or(false);
+                if is_python {
+                    candidates.push(keg.join("bin/python3"));
+                }
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let local_app_data = env::var("LOCALAPPDATA").unwrap_or_default();
+        for install in subdirs(&Path::new(&local_app_data).join(r"Programs\Python")) {
+            candidates.push(install.join("python.exe"));
+        }
+
+        let user_profile = env::var("USERPROFILE").unwrap_or_default();
+        for conda in ["miniconda3", "anaconda3", "miniforge3"] {
+            let root = Path::new(&user_profile).join(conda);
+            candidates.push(root.join("python.exe"));
+            for conda_env in subdirs(&root.join("envs")) {
+                candidates.push(conda_env.join("python.exe"));
+            }
+        }
+    }
+
+    // Dedup by resolved path; python3 is usually a symlink to python3.X in the same dir
+    let mut seen = std::collections::HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|path| is_executable(path))
+        .filter(|path| seen.insert(std::fs::canonicalize(path).unwrap_or_else(|_| path.clone())))
+        .collect()
+}
+
+// Ask an interpreter for its version; Python 2 prints it to stderr
+fn probe_version(path: &std::path::Path) -> Result<String, String> {
+    let output = std::process::Command::new(path)
+        .arg("--version")
+        .output_with_timeout(VERSION_PROBE_TIMEOUT)
+        .map_err(|e| format!("Failed to run {} --version: {}", path.display(), e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} --version failed: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let version = if output.stdout.is_empty() { &output.stderr } else { &output.stdout };
+    let version = String::from_utf8_lossy(version).trim().to_string();
+    if version.is_empty() {
+        return Err(format!("{} --version printed nothing", path.display()));
+    }
+    Ok(version)
+}
+
+// List every Python interpreter we can find that responds to --version, for an interpreter picker
+#[tauri::command]
+async fn list_python_interpreters() -> Result<Vec<PythonInterpreter>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let candidates = python_candidates();
+
+        // Probe in parallel; some interpreters (first-run conda, pyenv shims) are slow to start
+        std::thread::scope(|scope| {
+            let probes: Vec<_> = candidates
+                .iter()
+                .map(|path| scope.spawn(move || probe_version(path).ok()))
+                .collect();
+
+            candidates
+                .iter()
+                .zip(probes)
+                .filter_map(|(path, probe)| {
+                    let version = probe.join().ok()??;
+                    Some(PythonInterpreter {
+                        path: path.display().to_string(),
+                        version,
+                    })
+                })
+                .collect()
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to list Python interpreters: {}", e))
+}
+
+#[derive(Serialize)]
+pub struct RuntimeValidation {
+    path: String,  // Canonical path, symlinks resolved
+    exists: bool,
+    executable: bool,
+    version: Option<String>,
+    error: Option<String>,  // Why the path can't be adopted; None = valid
+}
+
+// Validate a Node/Python executable picked by the user before adopting it:
+// it must exist, be executable and answer --version like the expected runtime
+#[tauri::command]
+async fn validate_runtime_path(kind: String, path: String) -> Result<RuntimeValidation, String> {
+    // What `--version` starts with: "v20.11.0" / "Python 3.12.1"
+    let version_prefix = match kind.as_str() {
+        "node" => "v",
+        "python" => "Python",
+        _ => return Err(format!("Unknown runtime kind '{}', expected node or python", kind)),
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = std::path::PathBuf::from(path);
+        let canonical = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        let mut validation = RuntimeValidation {
+            path: canonical.display().to_string(),
+            exists: path.exists(),
+            executable: is_executable(&path),
+            version: None,
+            error: None,
+        };
+
+        if !validation.exists {
+            validation.error = Some(format!("{} does not exist", path.display()));
+        } else if !validation.executable {
+            validation.error = Some(format!("{} is not an executable file", path.display()));
+        } else {
+            match probe_version(&path) {
+                Ok(version) if version.starts_with(version_prefix) => {
+                    validation.version = Some(version);
+                }
+                Ok(version) => {
+                    validation.error = Some(format!(
+                        "{} does not look like {} (--version printed {:?})",
+                        path.display(),
+                        kind,
+                        version
+                    ));
+                    validation.version = Some(version);
+                }
+                Err(e) => validation.error = Some(e),
+            }
+        }
+        validation
+    })
+    .await
+    .map_err(|e| format!("Failed to validate runtime path: {}", e))
+}
+
+// Get the environment the sidecar would receive (inherited vars plus our overrides),
+// with values of sensitive-looking variables redacted
+#[tauri::command]
+async fn get_sidecar_environment(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+) -> Result<std::collections::BTreeMap<String, String>, String> {
+    let mut vars: std::collections::BTreeMap<String, String> = env::vars().collect();
+
+    let settings = settings.lock().await.clone();
+    let (path_entries, _) = sidecar_path_entries(settings.minimal_path);
+    let (token, _) = app.state::<secrets::SharedTokenCache>().get();
+    for (name, value) in backend_env(&path_entries, &settings, token.as_deref()) {
+        // Windows variable names are case-insensitive ("Path" vs "PATH")
+        #[cfg(target_os = "windows")]
+        vars.retain(|existing, _| !existing.eq_ignore_ascii_case(&name));
+        vars.insert(name, value);
+    }
+
+    for (name, value) in vars.iter_mut() {
+        if is_sensitive_env_var(name) {
+            *value = "<redacted>".to_string();
+        }
+    }
+
+    Ok(vars)
+}
+
+// Global packages that can shadow or conflict with what the backend imports or spawns
+const RELEVANT_NPM_PACKAGES: [&str; 1] = ["@anthropic-ai/claude-code"];
+const RELEVANT_PIP_PACKAGES: [&str; 10] = [
+    "claude-agent-sdk",
+    "fastapi",
+    "starlette",
+    "uvicorn",
+    "pydantic",
+    "pydantic-settings",
+    "anyio",
+    "boto3",
+    "aioboto3",
+    "aiosqlite",
+];
+
+// Listing global packages walks the whole global install, so allow more than a version probe
+const GLOBAL_PACKAGES_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Serialize)]
+pub struct GlobalPackage {
+    name: String,
+    version: String,
+    relevant: bool,  // One the backend depends on, so a global copy may conflict
+}
+
+#[derive(Serialize)]
+pub struct GlobalPackages {
+    npm: Vec<GlobalPackage>,
+    npm_error: Option<String>,
+    pip: Vec<GlobalPackage>,
+    pip_error: Option<String>,
+}
+
+// Run a package manager listing with the enhanced PATH and parse its JSON output
+fn list_packages_json(program: &std::path::Path, args: &[&str], path: &str) -> Result<serde_json::Value, String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .env("PATH", path)
+        .output_with_timeout(GLOBAL_PACKAGES_TIMEOUT)
+        .map_err(|e| format!("Failed to run {}: {}", program.display(), e))?;
+    // npm ls exits non-zero for problems like extraneous packages but still prints the tree
+    serde_json::from_slice(&output.stdout).map_err(|_| {
+        format!(
+            "{} {} failed: {}",
+            program.display(),
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+    })
+}
+
+// pip treats "-", "_" and "." in names as equivalent, case-insensitively
+fn normalize_pip_name(name: &str) -> String {
+    name.to_lowercase().replace(['_', '.'], "-")
+}
+
+// List globally installed npm packages and the pip packages of the Python on the enhanced PATH,
+// flagging those the backend also depends on
+#[tauri::command]
+async fn list_global_packages() -> Result<GlobalPackages, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let path_entries = get_enhanced_path_entries();
+        let path = path_entries.join(PATH_SEPARATOR);
+
+        let npm = find_executable("npm", &path_entries)
+            .ok_or_else(|| "npm not found on PATH".to_string())
+            .and_then(|npm| list_packages_json(&npm, &["ls", "-g", "--depth=0", "--json"], &path))
+            .map(|tree| {
+                tree["dependencies"]
+                    .as_object()
+                    .map(|dependencies| {
+                        dependencies
+                            .iter()
+                            .map(|(name, info)| GlobalPackage {
+                                name: name.clone(),
+                                version: info["version"].as_str().unwrap_or_default().to_string(),
+                                relevant: RELEVANT_NPM_PACKAGES.contains(&name.as_str()),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            });
+
+        let pip = resolve_system_python(None, &path_entries)
+            .and_then(|python| {
+                list_packages_json(
+                    std::path::Path::new(&python),
+                    &["-m", "pip", "list", "--format=json", "--disable-pip-version-check"],
+                    &path,
+                )
+            })
+            .map(|list| {
+                list.as_array()
+                    .map(|packages| {
+                        packages
+                            .iter()
+                            .filter_map(|package| {
+                                let name = package["name"].as_str()?;
+                                Some(GlobalPackage {
+                                    name: name.to_string(),
+                                    version: package["version"].as_str().unwrap_or_default().to_string(),
+                                    relevant: RELEVANT_PIP_PACKAGES.contains(&normalize_pip_name(name).as_str()),
+                                })
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            });
+
+        let (npm, npm_error) = match npm {
+            Ok(packages) => (packages, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        let (pip, pip_error) = match pip {
+            Ok(packages) => (packages, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        GlobalPackages {
+            npm,
+            npm_error,
+            pip,
+            pip_error,
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to list global packages: {}", e))
+}
+
+#[derive(Serialize)]
+pub struct ShimCheck {
+    path: String,
+    working: bool,
+    version: Option<String>,
+    output: Option<String>,  // What the shim printed when it failed
+}
+
+// Run the pyenv shims we put on the sidecar's PATH and report whether they work
+// A shim exists even when pyenv isn't initialized (or its selected version was uninstalled),
+// in which case Python looks discoverable but running it fails
+#[tauri::command]
+async fn check_pyenv_shims(settings: tauri::State<'_, SharedSettings>) -> Result<Vec<ShimCheck>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        let _ = settings;
+        return Err("Not applicable on this platform".to_string());
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    let minimal_path = settings.lock().await.minimal_path;
+    #[cfg(not(target_os = "windows"))]
+    tauri::async_runtime::spawn_blocking(move || {
+        let home = env::var("HOME").unwrap_or_default();
+        let shims = std::path::Path::new(&home).join(".pyenv/shims");
+        let (path_entries, _) = sidecar_path_entries(minimal_path);
+        let vars = sidecar_env(&path_entries);
+
+        ["python3", "python"]
+            .iter()
+            .map(|name| shims.join(name))
+            .filter(|shim| shim.exists())
+            .map(|shim| {
+                // Same environment the backend gets, so the shim finds (or fails to find) pyenv the same way
+                let result = std::process::Command::new(&shim)
+                    .arg("--version")
+                    .envs(vars.iter().map(|(name, value)| (name, value)))
+                    .output_with_timeout(VERSION_PROBE_TIMEOUT);
+                let mut check = ShimCheck {
+                    path: shim.display().to_string(),
+                    working: false,
+                    version: None,
+                    output: None,
+                };
+                match result {
+                    Ok(output) if output.status.success() => {
+                        let version = if output.stdout.is_empty() { &output.stderr } else { &output.stdout };
+                        check.version = Some(String::from_utf8_lossy(version).trim().to_string());
+                        check.working = true;
+                    }
+                    Ok(output) => {
+                        let printed = [output.stderr, output.stdout].concat();
+                        check.output = Some(format!(
+                            "Exited with {}: {}",
+                            output.status,
+                            String::from_utf8_lossy(&printed).trim()
+                        ));
+                    }
+                    Err(e) => check.output = Some(format!("Failed to run: {}", e)),
+                }
+                check
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to check pyenv shims: {}", e))
+}
+
+#[derive(Clone, Serialize)]
+pub struct EmulationInfo {
+    emulated: bool,
+    process_arch: String,  // Architecture the app and sidecar were built for
+    native_arch: String,   // Architecture of the machine
+}
+
+// Detect whether we run under emulation, e.g. an x64 build on Windows on ARM
+// IsWow64Process2 reports the native machine; the process arch is the one we were compiled for,
+// since the sidecar is bundled for the same target triple
+#[cfg(target_os = "windows")]
+fn detect_emulation() -> Option<EmulationInfo> {
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, IsWow64Process2};
+
+    let mut process_machine: u16 = 0;
+    let mut native_machine: u16 = 0;
+    let ok = unsafe { IsWow64Process2(GetCurrentProcess(), &mut process_machine, &mut native_machine) };
+    if ok == 0 {
+        return None;
+    }
+
+    let native_arch = match native_machine {
+        0x014c => "x86",
+        0x8664 => "x86_64",
+        0xaa64 => "aarch64",
+        0x01c4 => "arm",
+        _ => "unknown",
+    };
+    let process_arch = env::consts::ARCH;
+    Some(EmulationInfo {
+        emulated: native_arch != "unknown" && native_arch != process_arch,
+        process_arch: process_arch.to_string(),
+        native_arch: native_arch.to_string(),
+    })
+}
+
+#[cfg(not(target_os = "windows"))]
+fn detect_emulation() -> Option<EmulationInfo> {
+    None
+}
+
+// Report whether the app (and so the sidecar) runs under emulation (Windows only)
+#[tauri::command]
+async fn get_emulation_info() -> Result<EmulationInfo, String> {
+    #[cfg(not(target_os = "windows"))]
+    {
+        return Err("Not applicable on this platform".to_string());
+    }
+
+    #[cfg(target_os = "windows")]
+    detect_emulation().ok_or_else(|| "Failed to query the machine architecture".to_string())
+}
+
+#[derive(Serialize)]
+pub struct Diagnostics {
+    platform: String,
+    arch: String,
+    python_env: PythonEnv,
+    login_shell_fallback: bool,
+    enhanced_path: Vec<String>,
+    emulation: Option<EmulationInfo>,
+    python_runtime: settings::PythonRuntime,
+}
+
+// Interpreter for the system runtime: the configured one, else python3/python on the PATH we give the backend
+fn resolve_system_python(configured: Option<&str>, path_entries: &[String]) -> Result<String, String> {
+    if let Some(path) = configured {
+        return Ok(path.to_string());
+    }
+    ["python3", "python"]
+        .iter()
+        .find_map(|name| find_executable(name, path_entries))
+        .map(|path| path.display().to_string())
+        .ok_or_else(|| "No python3 or python found on PATH for the system Python runtime".to_string())
+}
+
+// How spawn_backend starts the backend process, per the configured runtime
+enum BackendLaunch {
+    Sidecar,
+    System { python: String, source_dir: String, host: String },
+    External { program: String, args: Vec<String> },
+}
+
+// Program and args for the external runtime, with "{port}"/"{host}" substituted
+// A bare program name is resolved on the PATH we give the backend
+fn resolve_external_command(
+    command: &[String],
+    host: &str,
+    port: u16,
+    path_entries: &[String],
+) -> Result<(String, Vec<String>), String> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| "No backend command configured for the external runtime".to_string())?;
+    let program = if program.contains(['/', '\\']) {
+        program.clone()
+    } else {
+        find_executable(program, path_entries)
+            .map(|path| path.display().to_string())
+            .ok_or_else(|| format!("{} not found on PATH for the external runtime", program))?
+    };
+    let args = args
+        .iter()
+        .map(|arg| arg.replace("{port}", &port.to_string()).replace("{host}", host))
+        .collect();
+    Ok((program, args))
+}
+
+// Switch (and persist) between the bundled sidecar, running the backend sources under a
+// system/custom Python, and launching a separately installed backend via external_command,
+// restarting a running backend to apply it
+#[tauri::command]
+async fn set_python_runtime(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+    runtime: settings::PythonRuntime,
+    python_path: Option<String>,
+    source_dir: Option<String>,
+    external_command: Option<Vec<String>>,
+) -> Result<(), String> {
+    let external_command = external_command.filter(|command| !command.is_empty());
+    if runtime == settings::PythonRuntime::System {
+        let dir = source_dir
+            .as_deref()
+            .ok_or_else(|| "The system runtime needs the backend source dir".to_string())?;
+        if !std::path::Path::new(dir).join("main.py").is_file() {
+            return Err(format!("{} does not contain the backend's main.py", dir));
+        }
+        if let Some(python) = python_path.as_deref() {
+            if !is_executable(std::path::Path::new(python)) {
+                return Err(format!("{} is not an executable file", python));
+            }
+        }
+    }
+
+    {
+        let mut settings = settings.lock().await;
+        let mut updated = settings.clone();
+        updated.python_runtime = runtime;
+        if runtime == settings::PythonRuntime::System {
+            updated.system_python_path = python_path;
+            updated.backend_source_dir = source_dir;
+        }
+        if let Some(command) = external_command {
+            updated.external_command = command;
+        }
+        if runtime == settings::PythonRuntime::External && updated.external_command.is_empty() {
+            return Err("The external runtime needs a backend command".to_string());
+        }
+        settings::save(&app, &updated)?;
+        *settings = updated;
+    }
+
+    if state.lock().await.running {
+        restart(&app).await?;
+    }
+    Ok(())
+}
+
+// Collect environment diagnostics for support and the settings UI
+#[tauri::command]
+async fn get_diagnostics(settings: tauri::State<'_, SharedSettings>) -> Result<Diagnostics, String> {
+    let settings = settings.lock().await.clone();
+
+    Ok(Diagnostics {
+        platform: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        python_env: detect_python_env(),
+        login_shell_fallback: settings.login_shell_fallback,
+        enhanced_path: get_enhanced_path_entries(),
+        emulation: detect_emulation(),
+        python_runtime: settings.python_runtime,
+    })
+}
+
+#[derive(Serialize)]
+pub struct PathScanTiming {
+    duration_ms: f64,
+    directories_scanned: usize,
+    entries: usize,
+}
+
+// Time a fresh enhanced PATH computation, to judge whether its filesystem scanning is costly here
+#[tauri::command]
+async fn measure_path_scan() -> Result<PathScanTiming, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let started = Instant::now();
+        let (entries, directories_scanned) = scan_enhanced_path_entries();
+        PathScanTiming {
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+            directories_scanned,
+            entries: entries.len(),
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to measure PATH scan: {}", e))
+}
+
+// Compute the minimal PATH: only directories containing node, python or git
+#[tauri::command]
+async fn get_minimal_path() -> Result<Vec<String>, String> {
+    tauri::async_runtime::spawn_blocking(get_minimal_path_entries)
+        .await
+        .map_err(|e| format!("Failed to compute minimal PATH: {}", e))
+}
+
+// Set (and persist) whether the sidecar gets the minimal PATH instead of the full enhanced one
+#[tauri::command]
+async fn set_minimal_path(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.minimal_path = enabled;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct SidecarPathList {
+    entries: Vec<String>,
+    dropped: Vec<String>,  // Left out to stay within the Windows environment variable length limit
+}
+
+// Get the ordered list of PATH entries the sidecar receives, honoring the minimal PATH setting
+// and the same last-resort system directories launch_backend falls back to
+#[tauri::command]
+async fn get_enhanced_path_list(settings: tauri::State<'_, SharedSettings>) -> Result<SidecarPathList, String> {
+    let minimal_path = settings.lock().await.minimal_path;
+    tauri::async_runtime::spawn_blocking(move || {
+        let (entries, dropped) = usable_sidecar_path_entries(minimal_path);
+        SidecarPathList { entries, dropped }
+    })
+    .await
+    .map_err(|e| format!("Failed to compute PATH: {}", e))
+}
+
+// Resolve an executable (e.g. "rg", "ffmpeg", "docker") against the PATH the sidecar gets
+// Returns its absolute path, or None if it can't be found
+#[tauri::command]
+async fn which_on_enhanced_path(
+    settings: tauri::State<'_, SharedSettings>,
+    name: String,
+) -> Result<Option<String>, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return Err(format!("Invalid executable name: {}", name));
+    }
+
+    let minimal_path = settings.lock().await.minimal_path;
+    tauri::async_runtime::spawn_blocking(move || {
+        let (entries, _) = usable_sidecar_path_entries(minimal_path);
+        find_executable(&name, &entries).map(|path| path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("Failed to search PATH: {}", e))
+}
+
+// Get the health-check path used by ping_backend
+#[tauri::command]
+async fn get_health_path(settings: tauri::State<'_, SharedSettings>) -> Result<String, String> {
+    let settings = settings.lock().await;
+    Ok(settings.health_path.clone())
+}
+
+// Set (and persist) the health-check path, e.g. "/health", "/healthz" or "/api/ping"
+#[tauri::command]
+async fn set_health_path(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    path: String,
+) -> Result<(), String> {
+    let path = path.trim().to_string();
+    settings::validate_url_path(&path)?;
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.health_path = path;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Set (and persist) the "status" value a health response must contain to count as our backend
+// Pass no status to accept any 2xx response
+#[tauri::command]
+async fn set_health_expected_status(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    status: Option<String>,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.health_expected_status = status.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Set (and persist) the scheme ("http" or "https") and host used for health checks
+#[tauri::command]
+async fn set_health_endpoint(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    scheme: Option<String>,
+    host: Option<String>,
+) -> Result<(), String> {
+    let scheme = scheme.map(|s| s.trim().to_lowercase());
+    if let Some(scheme) = &scheme {
+        if !settings::SUPPORTED_SCHEMES.contains(&scheme.as_str()) {
+            return Err(format!("Unsupported scheme '{}', expected http or https", scheme));
+        }
+    }
+    let host = host.map(|h| h.trim().to_string());
+    if let Some(host) = &host {
+        if host.is_empty() || host.contains(['/', ' ']) {
+            return Err(format!("Invalid host: {}", host));
+        }
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    if let Some(scheme) = scheme {
+        updated.health_scheme = scheme;
+    }
+    if let Some(host) = host {
+        updated.health_host = host;
+    }
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Enable or disable automatic restart after unexpected backend exits
+#[tauri::command]
+async fn set_auto_restart(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    enabled: bool,
+    max_attempts: Option<u32>,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.auto_restart = enabled;
+    if let Some(max_attempts) = max_attempts {
+        updated.max_restart_attempts = max_attempts;
+    }
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Configure (and persist) the graceful-shutdown endpoint stop_backend POSTs to before killing
+// Pass no path to disable it and kill the process directly
+#[tauri::command]
+async fn set_shutdown_endpoint(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    path: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<(), String> {
+    let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if let Some(path) = &path {
+        settings::validate_url_path(path)?;
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.shutdown_path = path;
+    if let Some(timeout_ms) = timeout_ms {
+        updated.shutdown_timeout_ms = timeout_ms;
+    }
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Enable or disable (and persist) crash report capture on abnormal backend exits
+#[tauri::command]
+async fn set_crash_reports_enabled(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.capture_crash_reports = enabled;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Set (and persist) extra sidecar args, common and per platform ("windows", "macos", "linux")
+// Applied the next time the backend starts
+#[tauri::command]
+async fn set_sidecar_args(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    extra_args: Option<Vec<String>>,
+    platform_args: Option<std::collections::HashMap<String, Vec<String>>>,
+) -> Result<(), String> {
+    if let Some(platform_args) = &platform_args {
+        if let Some(platform) = platform_args
+            .keys()
+            .find(|platform| !settings::SUPPORTED_PLATFORMS.contains(&platform.as_str()))
+        {
+            return Err(format!(
+                "Unknown platform '{}', expected one of: {}",
+                platform,
+                settings::SUPPORTED_PLATFORMS.join(", ")
+            ));
+        }
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    if let Some(extra_args) = extra_args {
+        updated.extra_args = extra_args;
+    }
+    if let Some(platform_args) = platform_args {
+        updated.platform_args = platform_args;
+    }
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Enable or disable (and persist) starting the backend automatically on app launch
+#[tauri::command]
+async fn set_auto_start_backend(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.auto_start_backend = enabled;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Enable or disable (and persist) the `$SHELL -l -c` fallback used by the version checks
+// Disabling it avoids slow or side-effecting shell profiles, at the cost of relying on the enhanced PATH
+#[tauri::command]
+async fn set_login_shell_fallback(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.login_shell_fallback = enabled;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Set (and persist) the watchdog interval in seconds; pass none to disable the watchdog
+#[tauri::command]
+async fn set_watchdog_interval(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    interval_secs: Option<u64>,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.watchdog_interval_secs = interval_secs.filter(|secs| *secs > 0);
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Set (and persist) the backend's memory and CPU caps; they apply from the next start
+#[tauri::command]
+async fn set_resource_limits(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    memory_limit_mb: Option<u64>,
+    cpu_limit_percent: Option<u32>,
+) -> Result<(), String> {
+    if let Some(percent) = cpu_limit_percent {
+        if !(1..=100).contains(&percent) {
+            return Err(format!("CPU limit must be between 1 and 100 percent: {}", percent));
+        }
+    }
+    if memory_limit_mb == Some(0) {
+        return Err("Memory limit must be greater than 0".to_string());
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.memory_limit_mb = memory_limit_mb;
+    updated.cpu_limit_percent = cpu_limit_percent;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Re-attach to a backend left running by a previous app instance (e.g. after the app crashed)
+// Returns its port, or None if there is nothing to recover and a new backend should be spawned
+#[tauri::command]
+async fn recover_previous_backend(app: tauri::AppHandle) -> Result<Option<u16>, String> {
+    recover_backend(&app).await
+}
+
+async fn recover_backend(app: &tauri::AppHandle) -> Result<Option<u16>, String> {
+    let state = app.state::<SharedBackendState>();
+    let settings = app.state::<SharedSettings>();
+    if state.lock().await.running {
+        return Err("Backend is already running".to_string());
+    }
+
+    let Some(previous) = snapshot::load(app) else {
+        return Ok(None);
+    };
+
+    // The pid may have been reused by an unrelated process since the snapshot was written, so
+    // only a process that also started at the recorded time is ours. Older snapshots have no
+    // start time and can't be verified
+    let pid = previous.pid;
+    let start_time = tauri::async_runtime::spawn_blocking(move || process::start_time(pid))
+        .await
+        .ok()
+        .flatten();
+    if previous.start_time.is_none() || start_time != previous.start_time {
+        snapshot::clear(app);
+        return Ok(None);
+    }
+
+    let (url, expected_status) = {
+        let settings = settings.lock().await;
+        (settings.health_url(previous.port), settings.health_expected_status.clone())
+    };
+    if !health::check_health(&url, expected_status.as_deref(), health::HEALTH_TIMEOUT)
+        .await
+        .unwrap_or(false)
+    {
+        // Our backend, but unusable: stop it so the new one doesn't run alongside an orphan
+        let _ = tauri::async_runtime::spawn_blocking(move || {
+            for child in process::descendants(pid).unwrap_or_default().iter().rev() {
+                process::kill(child.pid);
+            }
+            process::kill(pid);
+        })
+        .await;
+        snapshot::clear(app);
+        return Ok(None);
+    }
+
+    {
+        let mut backend = state.lock().await;
+        if backend.running {
+            return Err("Backend is already running".to_string());
+        }
+        // No child handle or output stream: stop_backend kills it by pid and the
+        // watchdog notices if it dies
+        backend.child = None;
+        backend.port = previous.port;
+        backend.pid = Some(previous.pid);
+        backend.running = true;
+        backend.started_at = Some(Instant::now());
+        backend.capabilities = None;
+        backend.forwarded_env = None;
+    }
+
+    let _ = app.emit("backend-recovered", previous.clone());
+    Ok(Some(previous.port))
+}
+
+// Limits concurrent one-shot sidecar invocations so maintenance commands can't stampede
+pub struct CommandLimiter {
+    semaphore: Arc<tokio::sync::Semaphore>,
+    limit: usize,
+    // Invocations waiting for a slot, in the semaphore's (FIFO) order; behind a std mutex so a
+    // cancelled waiter can leave it from Drop
+    queue: Arc<std::sync::Mutex<CommandQueue>>,
+}
+
+#[derive(Default)]
+struct CommandQueue {
+    next_id: u64,
+    waiting: std::collections::VecDeque<(u64, Vec<String>)>,
+}
+
+impl CommandLimiter {
+    fn new(limit: usize) -> Self {
+        Self {
+            semaphore: Arc::new(tokio::sync::Semaphore::new(limit)),
+            limit,
+            queue: Arc::default(),
+        }
+    }
+
+    // Resize the semaphore in place so queued invocations keep their place
+    // Slots held by running commands can't be revoked, so whatever part of a shrink idle slots
+    // can't cover is taken out of circulation as those commands finish
+    fn set_limit(&mut self, limit: usize) {
+        if limit > self.limit {
+            self.semaphore.add_permits(limit - self.limit);
+        } else if limit < self.limit {
+            let excess = self.limit - limit;
+            let outstanding = excess - self.semaphore.forget_permits(excess);
+            if outstanding > 0 {
+                let semaphore = self.semaphore.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Ok(permits) = semaphore.acquire_many_owned(outstanding as u32).await {
+                        permits.forget();
+                    }
+                });
+            }
+        }
+        self.limit = limit;
+    }
+}
+
+// An invocation's place in the command queue, given up when dropped: once it got its slot, or
+// when the caller was cancelled while waiting. Waiters behind it are sent their new position
+struct QueuedCommand {
+    app: tauri::AppHandle,
+    queue: Arc<std::sync::Mutex<CommandQueue>>,
+    id: u64,
+}
+
+impl QueuedCommand {
+    fn join(app: &tauri::AppHandle, queue: &Arc<std::sync::Mutex<CommandQueue>>, args: &[String]) -> Self {
+        let mut waiting = queue.lock().unwrap_or_else(|e| e.into_inner());
+        let id = waiting.next_id;
+        waiting.next_id += 1;
+        waiting.waiting.push_back((id, args.to_vec()));
+        let _ = app.emit("backend-command-queued", BackendCommandQueued {
+            id,
+            args: args.to_vec(),
+            position: waiting.waiting.len(),
+        });
+        Self {
+            app: app.clone(),
+            queue: queue.clone(),
+            id,
+        }
+    }
+}
+
+impl Drop for QueuedCommand {
+    fn drop(&mut self) {
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(index) = queue.waiting.iter().position(|(id, _)| *id == self.id) else {
+            return;
+        };
+        queue.waiting.remove(index);
+        for (position, (id, args)) in queue.waiting.iter().enumerate().skip(index) {
+            let _ = self.app.emit("backend-command-queued", BackendCommandQueued {
+                id: *id,
+                args: args.clone(),
+                position: position + 1,
+            });
+        }
+    }
+}
+
+type SharedCommandLimiter = Arc<Mutex<CommandLimiter>>;
+
+// Default time a one-shot backend command may run before it is killed
+const BACKEND_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Serialize)]
+struct BackendCommandQueued {
+    id: u64,  // Same for every update about one invocation
+    args: Vec<String>,
+    position: usize,  // 1 = next to run; re-sent whenever a waiter ahead leaves the queue
+}
+
+#[derive(Serialize)]
+pub struct BackendCommandOutput {
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+}
+
+// Fail commands that run the bundled sidecar when there is none to run: builds with the
+// external-backend feature don't ship it, and the System/External runtimes don't use it
+async fn require_bundled_runtime(app: &tauri::AppHandle) -> Result<(), String> {
+    if cfg!(feature = "external-backend") {
+        return Err("This build does not bundle the backend".to_string());
+    }
+    match app.state::<SharedSettings>().lock().await.python_runtime {
+        settings::PythonRuntime::Bundled => Ok(()),
+        _ => Err("Only available with the bundled backend runtime".to_string()),
+    }
+}
+
+// The bundled sidecar's --help text, read once per run to learn which options and subcommands
+// it accepts (its argparse exits with status 2 on anything unknown)
+type SharedSidecarHelp = Arc<tokio::sync::OnceCell<String>>;
+
+async fn sidecar_help(app: &tauri::AppHandle) -> Result<String, String> {
+    let cell = app.state::<SharedSidecarHelp>().inner().clone();
+    cell.get_or_try_init(|| async {
+        let (mut rx, child) = app
+            .shell()
+            .sidecar("python-backend")
+            .map_err(|e| format!("Failed to create sidecar command: {}", e))?
+            .arg("--help")
+            .spawn()
+            .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
+
+        let collect = async {
+            use tauri_plugin_shell::process::CommandEvent;
+            let mut help = String::new();
+            let mut code = None;
+            while let Some(event) = rx.recv().await {
+                match event {
+                    CommandEvent::Stdout(line) => help.push_str(&String::from_utf8_lossy(&line)),
+                    CommandEvent::Terminated(payload) => {
+                        code = payload.code;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            (code, help)
+        };
+        match tokio::time::timeout(BACKEND_COMMAND_TIMEOUT, collect).await {
+            Ok((Some(0), help)) => Ok(help),
+            Ok((code, _)) => Err(format!("Sidecar --help exited with code {:?}", code)),
+            Err(_) => {
+                let _ = child.kill();
+                Err("Sidecar --help timed out".to_string())
+            }
+        }
+    })
+    .await
+    .cloned()
+}
+
+// Whether the bundled sidecar's CLI accepts an option or subcommand (e.g. "--concurrency", "doctor")
+async fn sidecar_supports(app: &tauri::AppHandle, name: &str) -> Result<bool, String> {
+    let help = sidecar_help(app).await?;
+    Ok(help
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+        .any(|word| word == name))
+}
+
+// Whether the bundled sidecar accepts --detachable, as recorded by an earlier probe
+// Reading the record doesn't run the sidecar, so it is cheap enough to check on every spawn
+fn cached_detach_support(app: &tauri::AppHandle) -> Option<bool> {
+    snapshot::load_detach_support(app, &app.package_info().version.to_string())
+}
+
+// Probe the sidecar for --detachable in the background and record the answer for later spawns
+// A failed probe isn't recorded, so the next spawn tries again
+fn probe_detach_support(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match sidecar_supports(&app, DETACH_ARG).await {
+            Ok(supported) => {
+                let version = app.package_info().version.to_string();
+                if let Err(e) = snapshot::save_detach_support(&app, &version, supported) {
+                    log::warn!("{}", e);
+                }
+            }
+            Err(e) => log::warn!("Failed to probe the sidecar for {}: {}", DETACH_ARG, e),
+        }
+    });
+}
+
+// Take one of the limiter's slots for a one-shot sidecar invocation, held until the permit drops
+// When all are taken, wait (emitting backend-command-queued) or, without `wait`, fail immediately
+async fn acquire_command_permit(
+    app: &tauri::AppHandle,
+    limiter: &SharedCommandLimiter,
+    args: &[String],
+    wait: bool,
+) -> Result<tokio::sync::OwnedSemaphorePermit, String> {
+    let (semaphore, limit, queue) = {
+        let limiter = limiter.lock().await;
+        (limiter.semaphore.clone(), limiter.limit, limiter.queue.clone())
+    };
+
+    match semaphore.clone().try_acquire_owned() {
+        Ok(permit) => Ok(permit),
+        Err(_) if !wait => Err(format!(
+            "Too many backend commands running (limit {}); try again later",
+            limit
+        )),
+        Err(_) => {
+            let _place = QueuedCommand::join(app, &queue, args);
+            semaphore
+                .acquire_owned()
+                .await
+                .map_err(|_| "Backend command limiter was closed".to_string())
+        }
+    }
+}
+
+// Run the sidecar once with the given args (e.g. ["--version"], ["doctor"]) and collect its output
+// At most max_concurrent_backend_commands run at once; extras wait their turn (emitting
+// backend-command-queued) unless `wait` is false, in which case they fail immediately
+#[tauri::command]
+async fn run_backend_command(
+    app: tauri::AppHandle,
+    limiter: tauri::State<'_, SharedCommandLimiter>,
+    args: Vec<String>,
+    wait: Option<bool>,
+    timeout_ms: Option<u64>,
+) -> Result<BackendCommandOutput, String> {
+    require_bundled_runtime(&app).await?;
+    let _permit = acquire_command_permit(&app, &limiter, &args, wait.unwrap_or(true)).await?;
+
+    let settings = app.state::<SharedSettings>().lock().await.clone();
+    let (path_entries, _) = sidecar_path_entries(settings.minimal_path);
+    let (token, _) = app.state::<secrets::SharedTokenCache>().get();
+    let (mut rx, child) = app
+        .shell()
+        .sidecar("python-backend")
+        .map_err(|e| format!("Failed to create sidecar command: {}", e))?
+        .args(&args)
+        .envs(backend_env(&path_entries, &settings, token.as_deref()))
+        .spawn()
+        .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
+
+    let collect = async {
+        use tauri_plugin_shell::process::CommandEvent;
+        let mut output = BackendCommandOutput {
+            code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+        };
+        let stats = logs::DecodeStats::default();
+        let mut stdout_decoder = logs::LineDecoder::redacting(token.clone());
+        let mut stderr_decoder = logs::LineDecoder::redacting(token.clone());
+        while let Some(event) = rx.recv().await {
+            let (text, decoder, bytes) = match event {
+                CommandEvent::Stdout(bytes) => (&mut output.stdout, &mut stdout_decoder, bytes),
+                CommandEvent::Stderr(bytes) => (&mut output.stderr, &mut stderr_decoder, bytes),
+                CommandEvent::Terminated(payload) => {
+                    output.code = payload.code;
+                    for (text, decoder) in [
+                        (&mut output.stdout, &mut stdout_decoder),
+                        (&mut output.stderr, &mut stderr_decoder),
+                    ] {
+                        if let Some(line) = decoder.flush(0, &stats) {
+                            text.push_str(&line);
+                            text.push('\n');
+                        }
+                    }
+                    break;
+                }
+                _ => continue,
+            };
+            if let Some(line) = decoder.decode(&bytes, 0, &stats) {
+                text.push_str(&line);
+                text.push('\n');
+            }
+        }
+        output
+    };
+
+    let timeout = timeout_ms.map(Duration::from_millis).unwrap_or(BACKEND_COMMAND_TIMEOUT);
+    match tokio::time::timeout(timeout, collect).await {
+        Ok(output) => Ok(output),
+        Err(_) => {
+            let _ = child.kill();
+            Err(format!("Backend command timed out after {}ms", timeout.as_millis()))
+        }
+    }
+}
+
+// Sidecar subcommand that installs/warms the backend's dependencies and exits
+const PREPARE_DEPENDENCIES_ARGS: [&str; 1] = ["prepare-dependencies"];
+const PREPARE_DEPENDENCIES_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+// The running prepare_backend_dependencies invocation, so it can be cancelled
+type SharedDependencyJob = Arc<Mutex<Option<CommandChild>>>;
+
+#[derive(Serialize)]
+pub struct DependencySetupResult {
+    success: bool,
+    cancelled: bool,
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+}
+
+// Install/warm the backend's dependencies up front (e.g. during onboarding) instead of on first use
+// Output is streamed as backend-deps-progress events; cancel with cancel_backend_dependencies
+#[tauri::command]
+async fn prepare_backend_dependencies(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    job: tauri::State<'_, SharedDependencyJob>,
+    timeout_ms: Option<u64>,
+) -> Result<DependencySetupResult, String> {
+    require_bundled_runtime(&app).await?;
+    if !sidecar_supports(&app, PREPARE_DEPENDENCIES_ARGS[0]).await? {
+        return Err("Dependency setup is not supported by this backend".to_string());
+    }
+    let settings = settings.lock().await.clone();
+    let (path_entries, _) = sidecar_path_entries(settings.minimal_path);
+    let (token, _) = app.state::<secrets::SharedTokenCache>().get();
+    let env_vars = backend_env(&path_entries, &settings, token.as_deref());
+
+    let mut job_slot = job.lock().await;
+    if job_slot.is_some() {
+        return Err("Dependency setup is already running".to_string());
+    }
+    let (mut rx, child) = app
+        .shell()
+        .sidecar("python-backend")
+        .map_err(|e| format!("Failed to create sidecar command: {}", e))?
+        .args(PREPARE_DEPENDENCIES_ARGS)
+        .envs(env_vars)
+        .spawn()
+        .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
+    *job_slot = Some(child);
+    drop(job_slot);
+
+    let collect = async {
+        use tauri_plugin_shell::process::CommandEvent;
+        let mut result = DependencySetupResult {
+            success: false,
+            cancelled: false,
+            code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+        };
+        let stats = logs::DecodeStats::default();
+        let mut stdout_decoder = logs::LineDecoder::redacting(token.clone());
+        let mut stderr_decoder = logs::LineDecoder::redacting(token.clone());
+        while let Some(event) = rx.recv().await {
+            let (output, decoder, bytes) = match event {
+                CommandEvent::Stdout(bytes) => (&mut result.stdout, &mut stdout_decoder, bytes),
+                CommandEvent::Stderr(bytes) => (&mut result.stderr, &mut stderr_decoder, bytes),
+                CommandEvent::Terminated(payload) => {
+                    result.code = payload.code;
+                    for (output, decoder) in [
+                        (&mut result.stdout, &mut stdout_decoder),
+                        (&mut result.stderr, &mut stderr_decoder),
+                    ] {
+                        if let Some(line) = decoder.flush(0, &stats) {
+                            output.push_str(&line);
+                            output.push('\n');
+                        }
+                    }
+                    break;
+                }
+                _ => continue,
+            };
+            let Some(line) = decoder.decode(&bytes, 0, &stats) else {
+                continue;
+            };
+            output.push_str(&line);
+            output.push('\n');
+            let _ = app.emit("backend-deps-progress", ProgressLine {
+                percent: progress_percent(&line),
+                line,
+            });
+        }
+        result
+    };
+
+    let timeout = timeout_ms.map(Duration::from_millis).unwrap_or(PREPARE_DEPENDENCIES_TIMEOUT);
+    let outcome = tokio::time::timeout(timeout, collect).await;
+
+    // cancel_backend_dependencies takes the child out of the slot
+    let child = job.lock().await.take();
+    let mut result = match outcome {
+        Ok(result) => result,
+        Err(_) => {
+            if let Some(child) = child {
+                let _ = child.kill();
+            }
+            return Err(format!("Dependency setup timed out after {}s", timeout.as_secs()));
+        }
+    };
+    result.cancelled = child.is_none();
+    result.success = !result.cancelled && result.code == Some(0);
+    Ok(result)
+}
+
+// Cancel a running prepare_backend_dependencies; returns whether one was running
+#[tauri::command]
+async fn cancel_backend_dependencies(job: tauri::State<'_, SharedDependencyJob>) -> Result<bool, String> {
+    match job.lock().await.take() {
+        Some(child) => {
+            child
+                .kill()
+                .map_err(|e| format!("Failed to stop dependency setup: {}", e))?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+// Sidecar subcommand that runs the backend's own diagnostics and exits
+// It is expected to print a JSON report ({"passed": bool, "checks": [...]} or a bare
+// array of checks), but plain-text output from older backends is still returned
+const SELF_TEST_ARGS: [&str; 2] = ["doctor", "--json"];
+const SELF_TEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SelfTestCheck {
+    name: String,
+    #[serde(alias = "ok")]
+    passed: bool,
+    #[serde(default, alias = "detail")]
+    message: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SelfTestJson {
+    Report {
+        #[serde(default, alias = "ok")]
+        passed: Option<bool>,
+        checks: Vec<SelfTestCheck>,
+    },
+    Checks(Vec<SelfTestCheck>),
+}
+
+#[derive(Serialize)]
+pub struct SelfTestReport {
+    // False when the backend has no doctor subcommand; nothing was run, which is not a failure
+    supported: bool,
+    passed: bool,
+    // Whether the output parsed as a JSON report; if not, checks is empty and output holds the text
+    structured: bool,
+    checks: Vec<SelfTestCheck>,
+    code: Option<i32>,
+    output: String,
+}
+
+// Parse the self-test report from stdout, which may be preceded by log lines, so
+// fall back to the last line that parses on its own
+fn parse_self_test_output(stdout: &str) -> Option<(Option<bool>, Vec<SelfTestCheck>)> {
+    let parse = |text: &str| match serde_json::from_str::<SelfTestJson>(text.trim()).ok()? {
+        SelfTestJson::Report { passed, checks } => Some((passed, checks)),
+        SelfTestJson::Checks(checks) => Some((None, checks)),
+    };
+    parse(stdout).or_else(|| {
+        stdout
+            .lines()
+            .rev()
+            .filter(|line| line.trim_start().starts_with(['{', '[']))
+            .find_map(parse)
+    })
+}
+
+// Run the backend's built-in self-test (doctor) and return its checks
+#[tauri::command]
+async fn run_backend_self_test(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    limiter: tauri::State<'_, SharedCommandLimiter>,
+    timeout_ms: Option<u64>,
+) -> Result<SelfTestReport, String> {
+    require_bundled_runtime(&app).await?;
+    if !sidecar_supports(&app, SELF_TEST_ARGS[0]).await? {
+        return Ok(SelfTestReport {
+            supported: false,
+            passed: false,
+            structured: false,
+            checks: Vec::new(),
+            code: None,
+            output: "This backend has no self-test (doctor) subcommand".to_string(),
+        });
+    }
+
+    let args: Vec<String> = SELF_TEST_ARGS.iter().map(|arg| arg.to_string()).collect();
+    let _permit = acquire_command_permit(&app, &limiter, &args, true).await?;
+    let settings = settings.lock().await.clone();
+    let (path_entries, _) = sidecar_path_entries(settings.minimal_path);
+    let (token, _) = app.state::<secrets::SharedTokenCache>().get();
+    let env_vars = backend_env(&path_entries, &settings, token.as_deref());
+
+    let (mut rx, child) = app
+        .shell()
+        .sidecar("python-backend")
+        .map_err(|e| format!("Failed to create sidecar command: {}", e))?
+        .args(&args)
+        .envs(env_vars)
+        .spawn()
+        .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
+
+    let collect = async {
+        use tauri_plugin_shell::process::CommandEvent;
+        let mut output = BackendCommandOutput {
+            code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+        };
+        let stats = logs::DecodeStats::default();
+        let mut stdout_decoder = logs::LineDecoder::redacting(token.clone());
+        let mut stderr_decoder = logs::LineDecoder::redacting(token.clone());
+        while let Some(event) = rx.recv().await {
+            let (text, decoder, bytes) = match event {
+                CommandEvent::Stdout(bytes) => (&mut output.stdout, &mut stdout_decoder, bytes),
+                CommandEvent::Stderr(bytes) => (&mut output.stderr, &mut stderr_decoder, bytes),
+                CommandEvent::Terminated(payload) => {
+                    output.code = payload.code;
+                    for (text, decoder) in [
+                        (&mut output.stdout, &mut stdout_decoder),
+                        (&mut output.stderr, &mut stderr_decoder),
+                    ] {
+                        if let Some(line) = decoder.flush(0, &stats) {
+                            text.push_str(&line);
+                            text.push('\n');
+                        }
+                    }
+                    break;
+                }
+                _ => continue,
+            };
+            if let Some(line) = decoder.decode(&bytes, 0, &stats) {
+                text.push_str(&line);
+                text.push('\n');
+            }
+        }
+        output
+    };
+
+    let timeout = timeout_ms.map(Duration::from_millis).unwrap_or(SELF_TEST_TIMEOUT);
+    let output = match tokio::time::timeout(timeout, collect).await {
+        Ok(output) => output,
+        Err(_) => {
+            let _ = child.kill();
+            return Err(format!("Backend self-test timed out after {}s", timeout.as_secs()));
+        }
+    };
+
+    let exited_cleanly = output.code == Some(0);
+    Ok(match parse_self_test_output(&output.stdout) {
+        Some((passed, checks)) => SelfTestReport {
+            supported: true,
+            // Without an overall verdict, every check has to pass
+            passed: exited_cleanly && passed.unwrap_or_else(|| checks.iter().all(|check| check.passed)),
+            structured: true,
+            checks,
+            code: output.code,
+            output: output.stderr.trim_end().to_string(),
+        },
+        None => SelfTestReport {
+            supported: true,
+            passed: exited_cleanly,
+            structured: false,
+            checks: Vec::new(),
+            code: output.code,
+            output: format!("{}{}", output.stdout, output.stderr).trim_end().to_string(),
+        },
+    })
+}
+
+// Set (and persist) how many backend commands may run at once
+// Commands already running keep their slots and queued ones their place in line
+#[tauri::command]
+async fn set_backend_command_limit(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    limiter: tauri::State<'_, SharedCommandLimiter>,
+    limit: usize,
+) -> Result<(), String> {
+    if limit == 0 {
+        return Err("Limit must be at least 1".to_string());
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.max_concurrent_backend_commands = limit;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+
+    limiter.lock().await.set_limit(limit);
+    Ok(())
+}
+
+// Set (and persist) the readiness polling timeouts used when starting the backend
+#[tauri::command]
+async fn set_readiness_timeouts(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    request_timeout_ms: u64,
+    startup_timeout_ms: u64,
+) -> Result<(), String> {
+    if request_timeout_ms == 0 || startup_timeout_ms == 0 {
+        return Err("Timeouts must be greater than 0".to_string());
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.readiness_timeout_ms = request_timeout_ms;
+    updated.startup_timeout_ms = startup_timeout_ms;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Set (and persist) per-platform startup timeouts, keyed by OS ("windows", "macos", "linux")
+#[tauri::command]
+async fn set_platform_startup_timeouts(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    timeouts: std::collections::HashMap<String, u64>,
+) -> Result<(), String> {
+    if let Some(platform) = timeouts
+        .keys()
+        .find(|platform| !settings::SUPPORTED_PLATFORMS.contains(&platform.as_str()))
+    {
+        return Err(format!(
+            "Unknown platform '{}', expected one of: {}",
+            platform,
+            settings::SUPPORTED_PLATFORMS.join(", ")
+        ));
+    }
+    if timeouts.values().any(|ms| *ms == 0) {
+        return Err("Timeouts must be greater than 0".to_string());
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.platform_startup_timeout_ms = timeouts;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Set (and persist) the startup timeout used until the backend has started once
+// (None = platform default)
+#[tauri::command]
+async fn set_first_launch_startup_timeout(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    timeout_ms: Option<u64>,
+) -> Result<(), String> {
+    if timeout_ms == Some(0) {
+        return Err("Timeout must be greater than 0".to_string());
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.first_launch_startup_timeout_ms = timeout_ms;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Set (and persist) the startup timeout for the backend's first run against its data dir
+#[tauri::command]
+async fn set_first_run_startup_timeout(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    timeout_ms: u64,
+) -> Result<(), String> {
+    if timeout_ms == 0 {
+        return Err("Timeout must be greater than 0".to_string());
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.first_run_startup_timeout_ms = timeout_ms;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Get the configured max request body size in bytes (None = the backend's default)
+#[tauri::command]
+async fn get_max_body_size(settings: tauri::State<'_, SharedSettings>) -> Result<Option<u64>, String> {
+    Ok(settings.lock().await.max_body_size_bytes)
+}
+
+// Set (and persist) the backend's max request body size; it applies from the next start
+#[tauri::command]
+async fn set_max_body_size(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    bytes: Option<u64>,
+) -> Result<(), String> {
+    if let Some(bytes) = bytes {
+        settings::validate_body_size(bytes)?;
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.max_body_size_bytes = bytes;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Get the configured backend data directory (None = the backend's default location)
+#[tauri::command]
+async fn get_backend_data_dir(settings: tauri::State<'_, SharedSettings>) -> Result<Option<String>, String> {
+    Ok(settings.lock().await.backend_data_dir.clone())
+}
+
+// Set (and persist) the backend data directory; it applies from the next start
+// The directory is created if needed and must be writable
+#[tauri::command]
+async fn set_backend_data_dir(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    dir: Option<String>,
+) -> Result<(), String> {
+    if let Some(dir) = &dir {
+        let dir = dir.clone();
+        tauri::async_runtime::spawn_blocking(move || settings::validate_data_dir(&dir))
+            .await
+            .map_err(|e| format!("Failed to validate data directory: {}", e))??;
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.backend_data_dir = dir;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Where the backend keeps its data when no data dir is configured (mirrors backend/database/sqlite.py)
+fn default_backend_data_dir() -> std::path::PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        std::path::PathBuf::from(env::var("USERPROFILE").unwrap_or_default()).join(r"AppData\Local\Owork")
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        std::path::PathBuf::from(env::var("HOME").unwrap_or_default()).join("Library/Application Support/Owork")
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        std::path::PathBuf::from(env::var("HOME").unwrap_or_default()).join(".local/share/owork")
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct LockFileStatus {
+    path: String,
+    // Pid recorded in the file, if it holds one
+    pid: Option<u32>,
+    // The recorded pid is no longer running
+    stale: bool,
+    cleared: bool,
+}
+
+// Check the backend data dir for lock/pid files whose owning process is dead, optionally
+// removing them; call before start_backend after a crash
+// Files without a readable pid are reported but never removed, and SQLite journals
+// (data.db-journal, -wal) are left alone since deleting a hot journal corrupts the database
+#[tauri::command]
+async fn check_stale_lock_files(
+    settings: tauri::State<'_, SharedSettings>,
+    clear: bool,
+) -> Result<Vec<LockFileStatus>, String> {
+    let data_dir = settings
+        .lock()
+        .await
+        .backend_data_dir
+        .clone()
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(default_backend_data_dir);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let entries = match std::fs::read_dir(&data_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read {}: {}", data_dir.display(), e)),
+        };
+
+        let mut statuses = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_lock_file = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("lock") || ext.eq_ignore_ascii_case("pid"));
+            if !is_lock_file || !path.is_file() {
+                continue;
+            }
+
+            // Lock files conventionally hold the owner's pid, possibly followed by other fields
+            let pid = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|contents| contents.split_whitespace().next()?.parse::<u32>().ok());
+            let stale = pid.is_some_and(|pid| !process::is_process_alive(pid));
+            let cleared = stale && clear && std::fs::remove_file(&path).is_ok();
+            if cleared {
+                log::info!("Removed stale lock file {}", path.display());
+            }
+            statuses.push(LockFileStatus {
+                path: path.display().to_string(),
+                pid,
+                stale,
+                cleared,
+            });
+        }
+        Ok(statuses)
+    })
+    .await
+    .map_err(|e| format!("Failed to check lock files: {}", e))?
+}
+
+#[derive(Serialize)]
+pub struct CaseSensitivity {
+    location: &'static str,  // "app_data", "backend_data" or "working_dir"
+    path: String,
+    case_sensitive: Option<bool>,  // None if the directory couldn't be probed
+    error: Option<String>,
+}
+
+// Whether the filesystem holding dir treats names case-sensitively, found by creating a
+// lowercase probe file and looking it up by its uppercase name
+fn probe_case_sensitivity(dir: &std::path::Path) -> Result<bool, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let name = format!(".owork-case-probe-{:x}", fastrand::u64(..));
+    let probe = dir.join(&name);
+    std::fs::write(&probe, b"").map_err(|e| format!("Failed to write probe in {}: {}", dir.display(), e))?;
+    let case_sensitive = !dir.join(name.to_uppercase()).exists();
+    let _ = std::fs::remove_file(&probe);
+    Ok(case_sensitive)
+}
+
+// Report whether the app data, backend data and working directories are on case-sensitive
+// filesystems (macOS and Windows default to insensitive), to rule case-related backend bugs in or out
+#[tauri::command]
+async fn check_filesystem_case_sensitivity(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+) -> Result<Vec<CaseSensitivity>, String> {
+    let backend_data_dir = settings
+        .lock()
+        .await
+        .backend_data_dir
+        .clone()
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(default_backend_data_dir);
+    let locations = [
+        ("app_data", app.path().app_data_dir().map_err(|e| e.to_string())),
+        ("backend_data", Ok(backend_data_dir)),
+        ("working_dir", env::current_dir().map_err(|e| e.to_string())),
+    ];
+
+    tauri::async_runtime::spawn_blocking(move || {
+        locations
+            .into_iter()
+            .map(|(location, dir)| {
+                let (path, result) = match dir {
+                    Ok(dir) => (dir.display().to_string(), probe_case_sensitivity(&dir)),
+                    Err(e) => (String::new(), Err(format!("Failed to resolve directory: {}", e))),
+                };
+                CaseSensitivity {
+                    location,
+                    path,
+                    case_sensitive: result.as_ref().ok().copied(),
+                    error: result.err(),
+                }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to probe filesystems: {}", e))
+}
+
+// Check for other running copies of the app (same executable name, different pid)
+// The single-instance plugin stops new launches, but this also catches older versions
+// without it, which would otherwise fight over ports and state
+#[tauri::command]
+async fn is_another_instance_running() -> Result<bool, String> {
+    let exe = env::current_exe().map_err(|e| format!("Failed to resolve app executable: {}", e))?;
+    let exe_name = exe
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| "App executable has no file name".to_string())?;
+    let own_pid = std::process::id();
+
+    let processes = tauri::async_runtime::spawn_blocking(process::list_processes)
+        .await
+        .map_err(|e| format!("Failed to list processes: {}", e))??;
+
+    Ok(processes.iter().any(|p| {
+        // macOS reports the full path; Linux truncates the name to 15 chars
+        let name = std::path::Path::new(&p.name)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        p.pid != own_pid && (name == exe_name || (name.len() >= 15 && exe_name.starts_with(&name)))
+    }))
+}
+
+// Backend endpoint listing the (non-sensitive) names of env vars it consumed
+const ENV_REPORT_PATH: &str = "/env-report";
+
+#[derive(Serialize)]
+pub struct EnvReport {
+    consumed: Vec<String>,   // Reported by the backend
+    forwarded: Vec<String>,  // Set by us on the sidecar
+    unused: Vec<String>,     // Forwarded but not consumed
+}
+
+// Backend control endpoint for the request concurrency limit: GET reports it, POST changes it
+const CONCURRENCY_PATH: &str = "/control/concurrency";
+
+#[derive(Serialize)]
+pub struct BackendConcurrency {
+    configured: Option<u32>,  // Persisted setting, passed as --concurrency on start
+    current: Option<u64>,     // Reported by the running backend, if it supports the endpoint
+}
+
+// Get the backend's request concurrency limit
+#[tauri::command]
+async fn get_backend_concurrency(
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+) -> Result<BackendConcurrency, String> {
+    let (running, port) = {
+        let backend = state.lock().await;
+        (backend.running, backend.port)
+    };
+    let (configured, url) = {
+        let settings = settings.lock().await;
+        (settings.backend_concurrency, settings.endpoint_url(port, CONCURRENCY_PATH))
+    };
+
+    let current = if running {
+        health::get_json(&url, health::HEALTH_TIMEOUT)
+            .await
+            .ok()
+            .and_then(|body| body.get("concurrency").and_then(|value| value.as_u64()))
+    } else {
+        None
+    };
+    Ok(BackendConcurrency { configured, current })
+}
+
+// Set (and persist) the backend's request concurrency limit
+// Applied live through the control endpoint when the backend supports it, otherwise by
+// restarting it with --concurrency. Returns whether a restart was needed
+#[tauri::command]
+async fn set_backend_concurrency(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+    concurrency: u32,
+) -> Result<bool, String> {
+    if concurrency == 0 {
+        return Err("Concurrency must be greater than 0".to_string());
+    }
+    // The setting becomes a sidecar arg on every start, so never save one the sidecar would reject
+    let runtime = settings.lock().await.python_runtime;
+    if runtime == settings::PythonRuntime::Bundled && !sidecar_supports(&app, "--concurrency").await? {
+        return Err("The bundled backend does not support --concurrency".to_string());
+    }
+
+    let (running, port) = {
+        let backend = state.lock().await;
+        (backend.running, backend.port)
+    };
+    let url = {
+        let mut settings = settings.lock().await;
+        let mut updated = settings.clone();
+        updated.backend_concurrency = Some(concurrency);
+        settings::save(&app, &updated)?;
+        *settings = updated;
+        settings.endpoint_url(port, CONCURRENCY_PATH)
+    };
+
+    if !running {
+        return Ok(false);
+    }
+
+    let body = serde_json::json!({ "concurrency": concurrency });
+    if health::post_json(&url, &body, health::HEALTH_TIMEOUT).await? {
+        return Ok(false);
+    }
+    restart(&app).await?;
+    Ok(true)
+}
+
+// Backend endpoint describing its version, features and supported options
+const CAPABILITIES_PATH: &str = "/capabilities";
+
+// Fetch the backend's capabilities into the cache and announce them with backend-capabilities
+async fn refresh_capabilities(
+    app: tauri::AppHandle,
+    state: SharedBackendState,
+    port: u16,
+) -> Result<serde_json::Value, String> {
+    let url = app
+        .state::<SharedSettings>()
+        .lock()
+        .await
+        .endpoint_url(port, CAPABILITIES_PATH);
+    let capabilities = health::get_json(&url, health::HEALTH_TIMEOUT).await?;
+
+    {
+        let mut backend = state.lock().await;
+        // Don't cache the answer of a backend that has since been replaced
+        if !backend.running || backend.port != port {
+            return Err("Backend restarted while fetching capabilities".to_string());
+        }
+        backend.capabilities = Some(capabilities.clone());
+    }
+    let _ = app.emit("backend-capabilities", capabilities.clone());
+    Ok(capabilities)
+}
+
+// Capabilities declared by the running backend, cached from when it became ready
+// Lets the UI adapt to the backend version actually running; `refresh` refetches them
+#[tauri::command]
+async fn get_backend_capabilities(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedBackendState>,
+    refresh: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let port = {
+        let backend = state.lock().await;
+        if !backend.running {
+            return Err("Backend is not running".to_string());
+        }
+        if let (Some(capabilities), false) = (&backend.capabilities, refresh.unwrap_or(false)) {
+            return Ok(capabilities.clone());
+        }
+        backend.port
+    };
+
+    refresh_capabilities(app, state.inner().clone(), port).await
+}
+
+// Ask the backend which environment variables it read, and compare with what we forwarded
+#[tauri::command]
+async fn get_backend_env_report(
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+) -> Result<EnvReport, String> {
+    let (port, mut forwarded) = {
+        let backend = state.lock().await;
+        if !backend.running {
+            return Err("Backend is not running".to_string());
+        }
+        let forwarded = backend
+            .forwarded_env
+            .clone()
+            .ok_or_else(|| "The backend was started by an earlier session of the app".to_string())?;
+        (backend.port, forwarded)
+    };
+    let url = settings.lock().await.endpoint_url(port, ENV_REPORT_PATH);
+
+    // Accept either a bare array of names or {"vars": [...]}
+    let body = health::get_json(&url, health::HEALTH_TIMEOUT).await?;
+    let names = body.get("vars").unwrap_or(&body);
+    let mut consumed: Vec<String> = names
+        .as_array()
+        .ok_or_else(|| format!("Unexpected response from {}", url))?
+        .iter()
+        .filter_map(|name| name.as_str().map(|name| name.to_string()))
+        .collect();
+    consumed.sort();
+    forwarded.sort();
+
+    let unused = forwarded
+        .iter()
+        .filter(|name| !consumed.iter().any(|c| c.eq_ignore_ascii_case(name)))
+        .cloned()
+        .collect();
+
+    Ok(EnvReport {
+        consumed,
+        forwarded,
+        unused,
+    })
+}
+
+// Set (and persist) the port the backend should use when it is free (None = any free port)
+// OWORK_BACKEND_PORT still takes precedence when set
+#[tauri::command]
+async fn set_preferred_port(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    port: Option<u16>,
+) -> Result<(), String> {
+    if port == Some(0) {
+        return Err("Port must be greater than 0".to_string());
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.preferred_port = port;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Set (and persist) whether the backend keeps running after the window closes
+// The next launch reattaches to it instead of spawning a new one. Only a sidecar started with
+// this enabled can detach from our output pipes; any other backend is still stopped on close.
+// Returns whether the running backend must be restarted for the change to take effect
+#[tauri::command]
+async fn set_keep_backend_alive_on_close(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+    enabled: bool,
+) -> Result<bool, String> {
+    let running = state.lock().await.running;
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.keep_backend_alive_on_close = enabled;
+    settings::save(&app, &updated)?;
+    let requires_restart = settings.requires_restart(&updated);
+
+    // Learn whether the sidecar can detach before the restart needs to know
+    if enabled
+        && updated.python_runtime == settings::PythonRuntime::Bundled
+        && cached_detach_support(&app).is_none()
+    {
+        probe_detach_support(&app);
+    }
+    *settings = updated;
+    Ok(running && requires_restart)
+}
+
+// Set (and persist) whether devtools open automatically on a backend-fatal event
+#[tauri::command]
+async fn set_devtools_on_fatal(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.devtools_on_fatal = enabled;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Renice the backend and its descendants; listing those runs ps, so it runs off the runtime
+async fn apply_priority(pid: u32, nice: i32) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || limits::set_priority(pid, nice))
+        .await
+        .map_err(|e| format!("Failed to set priority: {}", e))?
+}
+
+// Set (and persist) the backend's priority as a niceness (0 = normal .. 19 = lowest)
+// Applied to the running backend right away and to every later start. None only stops
+// applying one at start; the running backend keeps the priority it has
+#[tauri::command]
+async fn set_backend_priority(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+    nice: Option<i32>,
+) -> Result<(), String> {
+    if let Some(nice) = nice {
+        if !(0..=limits::MAX_NICE).contains(&nice) {
+            return Err(format!("Niceness must be between 0 and {}: {}", limits::MAX_NICE, nice));
+        }
+    }
+
+    // Apply before saving: raising the priority back up can be refused (EACCES for
+    // unprivileged users), and then the old setting must stay
+    let pid = {
+        let backend = state.lock().await;
+        backend.pid.filter(|_| backend.running)
+    };
+    if let (Some(pid), Some(nice)) = (pid, nice) {
+        apply_priority(pid, nice).await?;
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.backend_nice = nice;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Report the fd limits the backend runs with and how many descriptors/handles it has open
+#[tauri::command]
+async fn get_fd_limits(state: tauri::State<'_, SharedBackendState>) -> Result<limits::FdLimits, String> {
+    let pid = state.lock().await.pid;
+    tauri::async_runtime::spawn_blocking(move || limits::fd_limits(pid))
+        .await
+        .map_err(|e| format!("Failed to read fd limits: {}", e))?
+}
+
+#[derive(Clone, Serialize)]
+pub struct ResourceSample {
+    cpu: f64,  // Percent of one core over the last interval, so may exceed 100 on multi-core machines
+    rss: u64,  // Resident memory in bytes
+    timestamp: u64,  // Milliseconds since the Unix epoch
+}
+
+const DEFAULT_RESOURCE_SAMPLE_INTERVAL_MS: u64 = 1000;
+
+// Stream the backend's CPU and memory usage through `on_sample` every interval_ms
+// Returns immediately; sampling stops once the backend stops (or is replaced) or the channel closes
+#[tauri::command]
+async fn stream_backend_resource_usage(
+    state: tauri::State<'_, SharedBackendState>,
+    on_sample: tauri::ipc::Channel<ResourceSample>,
+    interval_ms: Option<u64>,
+) -> Result<(), String> {
+    let interval_ms = interval_ms.unwrap_or(DEFAULT_RESOURCE_SAMPLE_INTERVAL_MS);
+    if !(100..=60_000).contains(&interval_ms) {
+        return Err(format!("Sampling interval must be between 100 and 60000 ms: {}", interval_ms));
+    }
+    let pid = {
+        let backend = state.lock().await;
+        backend.pid.filter(|_| backend.running)
+    }
+    .ok_or_else(|| "Backend is not running".to_string())?;
+
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn(async move {
+        let sample = move || tauri::async_runtime::spawn_blocking(move || limits::process_usage(pid));
+        let Ok(Ok(mut previous)) = sample().await else {
+            return;
+        };
+        let mut previous_at = Instant::now();
+
+        loop {
+            tokio::time::sleep(Duration::from_millis(interval_ms)).await;
+            {
+                let backend = state.lock().await;
+                if !backend.running || backend.pid != Some(pid) {
+                    break;
+                }
+            }
+            let Ok(Ok(usage)) = sample().await else {
+                break;
+            };
+            let elapsed = previous_at.elapsed().as_secs_f64();
+            previous_at = Instant::now();
+            let cpu_secs = usage.cpu_time.saturating_sub(previous.cpu_time).as_secs_f64();
+
+            let sent = on_sample.send(ResourceSample {
+                cpu: if elapsed > 0.0 { cpu_secs / elapsed * 100.0 } else { 0.0 },
+                rss: usage.rss_bytes,
+                timestamp: logs::now_millis(),
+            });
+            if sent.is_err() {
+                break;
+            }
+            previous = usage;
+        }
+    });
+    Ok(())
+}
+
+// Enable or disable (and persist) raising the soft fd limit to the hard limit before spawning
+// Takes effect at the next backend start; disabling it doesn't lower an already raised limit
+#[tauri::command]
+async fn set_raise_fd_limit(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    enabled: bool,
+) -> Result<(), String> {
+    if enabled && cfg!(target_os = "windows") {
+        return Err("Not applicable on this platform".to_string());
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.raise_fd_limit = enabled;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+// Ping the running backend's health endpoint
+#[tauri::command]
+async fn ping_backend(
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+    timeout_ms: Option<u64>,
+) -> Result<bool, String> {
+    let port = {
+        let backend = state.lock().await;
+        if !backend.running {
+            return Err("Backend is not running".to_string());
+        }
+        backend.port
+    };
+    let (url, expected_status) = {
+        let settings = settings.lock().await;
+        (settings.health_url(port), settings.health_expected_status.clone())
+    };
+
+    let timeout = timeout_ms.map(Duration::from_millis).unwrap_or(health::HEALTH_TIMEOUT);
+    health::check_health(&url, expected_status.as_deref(), timeout).await
+}
+
+#[derive(Serialize)]
+pub struct BindInfo {
+    port: u16,
+    addresses: Vec<String>,  // Addresses listening on the port, e.g. "127.0.0.1" or "0.0.0.0"
+    loopback_only: bool,
+}
+
+// Report which addresses the backend listens on, so the UI can show a "local only"
+// indicator and warn when it is reachable from the network
+#[tauri::command]
+async fn get_backend_bind_info(state: tauri::State<'_, SharedBackendState>) -> Result<BindInfo, String> {
+    let port = {
+        let backend = state.lock().await;
+        if !backend.running {
+            return Err("Backend is not running".to_string());
+        }
+        backend.port
+    };
+
+    let mut addresses = tauri::async_runtime::spawn_blocking(move || process::listening_addresses(port))
+        .await
+        .map_err(|e| format!("Failed to inspect sockets: {}", e))??;
+    if addresses.is_empty() {
+        return Err(format!("Nothing is listening on port {}", port));
+    }
+    addresses.sort();
+    addresses.dedup();
+
+    // IPv4-mapped loopback (::ffff:127.0.0.1) counts as loopback too
+    let loopback_only = addresses.iter().all(|address| match address {
+        std::net::IpAddr::V6(v6) => v6.is_loopback() || v6.to_ipv4_mapped().map(|v4| v4.is_loopback()).unwrap_or(false),
+        address => address.is_loopback(),
+    });
+    Ok(BindInfo {
+        port,
+        addresses: addresses.iter().map(|address| address.to_string()).collect(),
+        loopback_only,
+    })
+}
+
+#[derive(Serialize)]
+pub struct PortAvailability {
+    port: u16,
+    available: bool,
+    error: Option<String>,  // Why binding failed, e.g. "Address already in use"
+}
+
+// Check up front that every port the backend and its subservices need can be bound
+// on loopback, so a taken secondary port doesn't surface as a failure later
+#[tauri::command]
+async fn check_ports_available(ports: Vec<u16>) -> Result<Vec<PortAvailability>, String> {
+    if ports.contains(&0) {
+        return Err("Port 0 is not a fixed port".to_string());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        ports
+            .into_iter()
+            .map(|port| match std::net::TcpListener::bind(("127.0.0.1", port)) {
+                Ok(_) => PortAvailability {
+                    port,
+                    available: true,
+                    error: None,
+                },
+                Err(e) => PortAvailability {
+                    port,
+                    available: false,
+                    error: Some(e.to_string()),
+                },
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to probe ports: {}", e))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortOwnership {
+    OurBackend,
+    ForeignProcess,  // Something accepts connections but doesn't answer like our backend
+    NotBound,
+}
+
+#[derive(Serialize)]
+pub struct WebviewReachability {
+    base_url: String,              // Origin the frontend should fetch the backend from
+    healthy: Option<bool>,         // From our side; None when the backend isn't running
+    csp: Option<String>,           // None = no CSP, so it can't block the backend
+    csp_directive: Option<String>, // Directive governing fetch(): connect-src, else default-src
+    csp_allows: bool,
+    csp_suggestion: Option<String>, // Source to add to the directive when it blocks the backend
+    localhost_addresses: Vec<std::net::IpAddr>,  // What "localhost" resolves to here
+    listening_addresses: Vec<std::net::IpAddr>,  // Where the backend actually listens
+    localhost_mismatch: bool,  // "localhost" may resolve to an address the backend doesn't listen on
+}
+
+// Diagnose whether the webview can reach a backend that we can reach from Rust:
+// the base URL to use, whether the app's CSP allows fetching it, and whether "localhost"
+// (which the frontend uses) resolves to an address the backend is listening on
+#[tauri::command]
+async fn check_webview_reachability(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+) -> Result<WebviewReachability, String> {
+    let (running, port) = {
+        let backend = state.lock().await;
+        (backend.running, backend.port)
+    };
+    let (base_url, health_url, expected_status) = {
+        let settings = settings.lock().await;
+        (
+            settings.endpoint_url(port, ""),
+            settings.health_url(port),
+            settings.health_expected_status.clone(),
+        )
+    };
+
+    let healthy = if running {
+        Some(
+            health::check_health(&health_url, expected_status.as_deref(), health::HEALTH_TIMEOUT)
+                .await
+                .unwrap_or(false),
+        )
+    } else {
+        None
+    };
+
+    // Development builds use devCsp when it is set
+    let security = &app.config().app.security;
+    let csp = if cfg!(debug_assertions) {
+        security.dev_csp.as_ref().or(security.csp.as_ref())
+    } else {
+        security.csp.as_ref()
+    }
+    .map(|csp| csp.to_string());
+    let (csp_directive, csp_allows) = match &csp {
+        Some(policy) => health::csp_allows_connect(policy, &base_url),
+        None => (None, true),
+    };
+    let csp_suggestion = (!csp_allows).then(|| {
+        let scheme = base_url.split("://").next().unwrap_or("http");
+        format!("{}://127.0.0.1:* {}://localhost:*", scheme, scheme)
+    });
+
+    let (localhost_addresses, listening_addresses) = tauri::async_runtime::spawn_blocking(move || {
+        use std::net::ToSocketAddrs;
+        let localhost = ("localhost", port)
+            .to_socket_addrs()
+            .map(|addrs| addrs.map(|addr| addr.ip()).collect::<Vec<_>>())
+            .unwrap_or_default();
+        (localhost, process::listening_addresses(port).unwrap_or_default())
+    })
+    .await
+    .map_err(|e| format!("Failed to resolve addresses: {}", e))?;
+
+    // A wildcard listener covers every address of its family
+    let listens_on = |address: &std::net::IpAddr| {
+        listening_addresses.iter().any(|listening| {
+            listening == address || (listening.is_unspecified() && listening.is_ipv4() == address.is_ipv4())
+        })
+    };
+    let localhost_mismatch = running
+        && !listening_addresses.is_empty()
+        && localhost_addresses.iter().any(|address| !listens_on(address));
+
+    Ok(WebviewReachability {
+        base_url,
+        healthy,
+        csp,
+        csp_directive,
+        csp_allows,
+        csp_suggestion,
+        localhost_addresses,
+        listening_addresses,
+        localhost_mismatch,
+    })
+}
+
+// Timing of the latest backend start: spawn to first output and to readiness
+// A long gap before the first output points to slow interpreter startup or AV scanning,
+// while an exit without any output points to a spawn that silently died
+#[tauri::command]
+async fn get_startup_timing(
+    state: tauri::State<'_, SharedBackendState>,
+) -> Result<Option<StartupTiming>, String> {
+    Ok(state.lock().await.startup_timing.clone())
+}
+
+// Check who owns a port (default: the backend's port) before attaching to it
+// A listener only counts as ours if it passes the health content check
+#[tauri::command]
+async fn check_port_ownership(
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+    port: Option<u16>,
+) -> Result<PortOwnership, String> {
+    let port = match port {
+        Some(port) => port,
+        None => state.lock().await.port,
+    };
+
+    let bound = tauri::async_runtime::spawn_blocking(move || is_port_connectable(port))
+        .await
+        .map_err(|e| format!("Failed to probe port: {}", e))?;
+    if !bound {
+        return Ok(PortOwnership::NotBound);
+    }
+
+    let (url, expected_status) = {
+        let settings = settings.lock().await;
+        (settings.health_url(port), settings.health_expected_status.clone())
+    };
+    match health::check_health(&url, expected_status.as_deref(), health::HEALTH_TIMEOUT).await {
+        Ok(true) => Ok(PortOwnership::OurBackend),
+        _ => Ok(PortOwnership::ForeignProcess),
+    }
+}
+
+// Compare the backend's clock with ours, for auth/token failures caused by a skewed clock
+// (typically an attached backend running in a container or on another machine)
+#[tauri::command]
+async fn check_clock_skew(
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+) -> Result<health::ClockSkew, String> {
+    let port = {
+        let backend = state.lock().await;
+        if !backend.running {
+            return Err("Backend is not running".to_string());
+        }
+        backend.port
+    };
+    let url = settings.lock().await.health_url(port);
+    health::clock_skew(&url, health::HEALTH_TIMEOUT).await
+}
+
+// Benchmark backend latency with a burst of health requests
+// Defaults to 20 sequential requests; concurrency is capped to keep the burst gentle
+#[tauri::command]
+async fn benchmark_backend(
+    state: tauri::State<'_, SharedBackendState>,
+    settings: tauri::State<'_, SharedSettings>,
+    requests: Option<u32>,
+    concurrency: Option<u32>,
+) -> Result<health::LatencyStats, String> {
+    let port = {
+        let backend = state.lock().await;
+        if !backend.running {
+            return Err("Backend is not running".to_string());
+        }
+        backend.port
+    };
+    let url = settings.lock().await.health_url(port);
+
+    let requests = requests.unwrap_or(20).clamp(1, 1000);
+    let concurrency = concurrency.unwrap_or(1).clamp(1, 16);
+    health::benchmark(&url, requests, concurrency).await
+}
+
+// List all descendant processes of the running backend (node tools, git, subprocesses, ...)
+#[tauri::command]
+async fn list_backend_children(
+    state: tauri::State<'_, SharedBackendState>,
+) -> Result<Vec<process::ProcessInfo>, String> {
+    let pid = state
+        .lock()
+        .await
+        .pid
+        .ok_or_else(|| "Backend is not running".to_string())?;
+
+    tauri::async_runtime::spawn_blocking(move || process::descendants(pid))
+        .await
+        .map_err(|e| format!("Failed to list processes: {}", e))?
+}
+
+// std::process::Command::output with a deadline, for tools that may hang (e.g. a login shell
+// whose profile waits for input). The child is killed when the timeout elapses
+// Blocks the calling thread, so async code runs it through spawn_blocking
+trait CommandTimeoutExt {
+    fn output_with_timeout(&mut self, timeout: Duration) -> std::io::Result<std::process::Output>;
+}
+
+impl CommandTimeoutExt for std::process::Command {
+    fn output_with_timeout(&mut self, timeout: Duration) -> std::io::Result<std::process::Output> {
+        use std::io::Read;
+        use std::process::Stdio;
+
+        let mut child = self
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Drain the pipes on threads so a chatty child can't block on a full pipe
+        fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> std::sync::mpsc::Receiver<Vec<u8>> {
+            let (tx, rx) = std::sync::mpsc::channel();
+            if let Some(mut pipe) = pipe {
+                std::thread::spawn(move || {
+                    let mut buf = Vec::new();
+                    let _ = pipe.read_to_end(&mut buf);
+                    let _ = tx.send(buf);
+                });
+            }
+            rx
+        }
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("timed out after {}ms", timeout.as_millis()),
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+
+        // A grandchild that inherited the pipes can hold them open after the child exits, so stop
+        // waiting for the readers at the deadline too; they finish on their own once it closes them
+        let collect = |reader: std::sync::mpsc::Receiver<Vec<u8>>| {
+            reader
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .unwrap_or_default()
+        };
+        Ok(std::process::Output {
+            status,
+            stdout: collect(stdout),
+            stderr: collect(stderr),
+        })
+    }
+}
+
+// Lines of shell stderr kept when reporting a failing profile
+#[cfg(not(target_os = "windows"))]
+const SHELL_ERROR_MAX_LINES: usize = 20;
+
+// Describe what a failed login-shell fallback printed on stderr, which is usually the user's
+// profile erroring out (e.g. "~/.zshrc:12: command not found: foo"), or None if it was silent
+#[cfg(not(target_os = "windows"))]
+fn shell_profile_error(shell: &str, output: &std::process::Output) -> Option<String> {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines: Vec<&str> = stderr.lines().filter(|line| !line.trim().is_empty()).collect();
+    if lines.is_empty() {
+        return None;
+    }
+
+    let tail = &lines[lines.len().saturating_sub(SHELL_ERROR_MAX_LINES)..];
+    Some(format!(
+        "Your login shell ({}) reported errors, which usually means a shell profile is broken:\n{}",
+        shell,
+        tail.join("\n")
+    ))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell_timeout_error(shell: &str, timeout: Duration) -> String {
+    format!(
+        "{} -l did not finish within {}s; a slow or interactive shell profile may be blocking",
+        shell,
+        timeout.as_secs()
+    )
+}
+
+// Set (and persist) the per-process timeout used by the version checks
 #[tauri::command]
-async fn get_backend_port(state: tauri::State<'_, SharedBackendState>) -> Result<u16, String> {
-    let backend = state.lock().await;
-    Ok(backend.port)
+async fn set_version_check_timeout(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    timeout_ms: u64,
+) -> Result<(), String> {
+    if timeout_ms == 0 {
+        return Err("Timeout must be greater than 0".to_string());
+    }
+
+    let mut settings = settings.lock().await;
+    let mut updated = settings.clone();
+    updated.version_check_timeout_ms = timeout_ms;
+    settings::save(&app, &updated)?;
+    *settings = updated;
+    Ok(())
 }
 
 // Check Node.js version
 #[tauri::command]
-async fn check_nodejs_version() -> Result<String, String> {
+async fn check_nodejs_version(
+    settings: tauri::State<'_, SharedSettings>,
+    cache: tauri::State<'_, SharedVersionCache>,
+) -> Result<String, String> {
+    let (login_shell_fallback, timeout) = {
+        let settings = settings.lock().await;
+        (settings.login_shell_fallback, settings.version_check_timeout())
+    };
+    let result = nodejs_version(login_shell_fallback, timeout).await;
+    cache.store("node", &result);
+    result
+}
+
+// Detect the Node.js version, optionally falling back to the user's login shell
+async fn nodejs_version(login_shell_fallback: bool, timeout: Duration) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || detect_nodejs_version(login_shell_fallback, timeout))
+        .await
+        .map_err(|e| format!("Node.js check failed: {}", e))?
+}
+
+fn detect_nodejs_version(
+    #[cfg_attr(target_os = "windows", allow(unused_variables))] login_shell_fallback: bool,
+    timeout: Duration,
+) -> Result<String, String> {
     // Try direct execution with enhanced PATH first (works on all platforms)
     let enhanced_path = get_enhanced_path();
 
@@ -366,7 +5629,7 @@ async fn check_nodejs_version() -> Result<String, String> {
     let output = std::process::Command::new(node_cmd)
         .arg("--version")
         .env("PATH", &enhanced_path)
-        .output();
+        .output_with_timeout(timeout);
 
     match output {
         Ok(output) if output.status.success() => {
@@ -380,14 +5643,41 @@ async fn check_nodejs_version() -> Result<String, String> {
 
     // On Unix systems, try using user's shell as fallback (for nvm, volta, etc.)
     #[cfg(not(target_os = "windows"))]
-    {
+    let mut shell_error = None;
+    #[cfg(not(target_os = "windows"))]
+    if login_shell_fallback {
         let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
 
         let output = std::process::Command::new(&shell)
             .arg("-l")  // Login shell to source profile
             .arg("-c")  // Execute command
             .arg("node --version")
-            .output();
+            .output_with_timeout(timeout);
+
+        // A hung profile would otherwise make every fallback attempt time out in turn
+        if let Err(e) = &output {
+            if e.kind() == std::io::ErrorKind::TimedOut {
+                return Err(shell_timeout_error(&shell, timeout));
+            }
+        }
+
+        if let Ok(output) = output {
+            if output.status.success() {
+                let version = String::from_utf8_lossy(&output.stdout)
+                    .trim()
+                    .to_string();
+                return Ok(version);
+            }
+            shell_error = shell_profile_error(&shell, &output);
+        }
+    }
+
+    // On Windows, try PowerShell as fallback
+    #[cfg(target_os = "windows")]
+    {
+        let output = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-Command", "node --version"])
+            .output_with_timeout(timeout);
 
         if let Ok(output) = output {
             if output.status.success() {
@@ -399,186 +5689,942 @@ async fn check_nodejs_version() -> Result<String, String> {
         }
     }
 
+    // Surface a broken profile rather than a misleading "not installed"
+    #[cfg(not(target_os = "windows"))]
+    if let Some(shell_error) = shell_error {
+        return Err(format!("Node.js was not found on PATH. {}", shell_error));
+    }
+
+    Err("Node.js is not installed or not in PATH".to_string())
+}
+
+// Check Git Bash path (Windows only)
+// Returns the path if CLAUDE_CODE_GIT_BASH_PATH is set and the file exists,
+// or tries to auto-detect Git Bash in common locations
+#[tauri::command]
+async fn check_git_bash_path() -> Result<String, String> {
+    // Only relevant on Windows
+    #[cfg(not(target_os = "windows"))]
+    {
+        return Err("Not applicable on this platform".to_string());
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // First check if CLAUDE_CODE_GIT_BASH_PATH is set
+        if let Ok(git_bash_path) = env::var("CLAUDE_CODE_GIT_BASH_PATH") {
+            if std::path::Path::new(&git_bash_path).exists() {
+                return Ok(git_bash_path);
+            }
+        }
+
+        // Try to auto-detect Git Bash in common locations
+        let common_paths = vec![
+            // Default Git for Windows installation paths
+            r"C:\Program Files\Git\bin\bash.exe",
+            r"C:\Program Files (x86)\Git\bin\bash.exe",
+        ];
+
+        // Also check LOCALAPPDATA and ProgramFiles
+        if let Ok(localappdata) = env::var("LOCALAPPDATA") {
+            let path = format!(r"{}\Programs\Git\bin\bash.exe", localappdata);
+            if std::path::Path::new(&path).exists() {
+                return Ok(path);
+            }
+        }
+
+        if let Ok(programfiles) = env::var("ProgramFiles") {
+            let path = format!(r"{}\Git\bin\bash.exe", programfiles);
+            if std::path::Path::new(&path).exists() {
+                return Ok(path);
+            }
+        }
+
+        for path in common_paths {
+            if std::path::Path::new(path).exists() {
+                return Ok(path.to_string());
+            }
+        }
+
+        Err("Git Bash not found".to_string())
+    }
+}
+
+// Check Python version
+#[tauri::command]
+async fn check_python_version(
+    settings: tauri::State<'_, SharedSettings>,
+    cache: tauri::State<'_, SharedVersionCache>,
+) -> Result<String, String> {
+    let (login_shell_fallback, timeout) = {
+        let settings = settings.lock().await;
+        (settings.login_shell_fallback, settings.version_check_timeout())
+    };
+    let result = python_version(login_shell_fallback, timeout).await;
+    cache.store("python", &result);
+    result
+}
+
+// Detect the Python version, optionally falling back to the user's login shell
+async fn python_version(login_shell_fallback: bool, timeout: Duration) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || detect_python_version(login_shell_fallback, timeout))
+        .await
+        .map_err(|e| format!("Python check failed: {}", e))?
+}
+
+fn detect_python_version(
+    #[cfg_attr(target_os = "windows", allow(unused_variables))] login_shell_fallback: bool,
+    timeout: Duration,
+) -> Result<String, String> {
+    let enhanced_path = get_enhanced_path();
+
+    // Windows uses python.exe, Unix uses python3 or python
+    #[cfg(target_os = "windows")]
+    let python_commands = vec!["python.exe", "python3.exe", "py.exe"];
+
+    #[cfg(not(target_os = "windows"))]
+    let python_commands = vec!["python3", "python"];
+
+    // Try each Python command with enhanced PATH
+    for cmd in &python_commands {
+        let output = std::process::Command::new(cmd)
+            .arg("--version")
+            .env("PATH", &enhanced_path)
+            .output_with_timeout(timeout);
+
+        if let Ok(output) = output {
+            if output.status.success() {
+                // Python 2.x writes version to stderr, Python 3.x to stdout
+                let version_str = if !output.stdout.is_empty() {
+                    String::from_utf8_lossy(&output.stdout)
+                } else {
+                    String::from_utf8_lossy(&output.stderr)
+                };
+
+                let version = version_str.trim().to_string();
+                if !version.is_empty() {
+                    return Ok(version);
+                }
+            }
+        }
+    }
+
+    // On Unix systems, try using user's shell as fallback (for pyenv, etc.)
+    #[cfg(not(target_os = "windows"))]
+    {
+        let home = env::var("HOME").unwrap_or_default();
+
+        let mut shell_error = None;
+        if login_shell_fallback {
+            let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
+
+            let output = std::process::Command::new(&shell)
+                .arg("-l")  // Login shell to source profile
+                .arg("-c")  // Execute command
+                .arg("python3 --version 2>&1 || python --version 2>&1")
+                .output_with_timeout(timeout);
+
+            if let Err(e) = &output {
+                if e.kind() == std::io::ErrorKind::TimedOut {
+                    return Err(shell_timeout_error(&shell, timeout));
+                }
+            }
+
+            if let Ok(output) = output {
+                if output.status.success() {
+                    let version = String::from_utf8_lossy(&output.stdout)
+                        .trim()
+                        .to_string();
+                    if !version.is_empty() {
+                        return Ok(version);
+                    }
+                }
+                shell_error = shell_profile_error(&shell, &output);
+            }
+        }
+
+        // Try pyenv directly if available
+        let pyenv_path = format!("{}/.pyenv/shims/python3", home);
+        if std::path::Path::new(&pyenv_path).exists() {
+            if let Ok(output) = std::process::Command::new(&pyenv_path)
+                .arg("--version")
+                .output_with_timeout(timeout) {
+                if output.status.success() {
+                    let version = String::from_utf8_lossy(&output.stdout)
+                        .trim()
+                        .to_string();
+                    return Ok(version);
+                }
+            }
+        }
+
+        // Surface a broken profile rather than a misleading "not installed"
+        if let Some(shell_error) = shell_error {
+            return Err(format!("Python was not found on PATH. {}", shell_error));
+        }
+    }
+
     // On Windows, try PowerShell as fallback
     #[cfg(target_os = "windows")]
     {
-        let output = std::process::Command::new("powershell")
-            .args(["-NoProfile", "-Command", "node --version"])
-            .output();
+        let output = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-Command", "python --version"])
+            .output_with_timeout(timeout);
+
+        if let Ok(output) = output {
+            if output.status.success() {
+                let version_str = if !output.stdout.is_empty() {
+                    String::from_utf8_lossy(&output.stdout)
+                } else {
+                    String::from_utf8_lossy(&output.stderr)
+                };
+                let version = version_str.trim().to_string();
+                if !version.is_empty() {
+                    return Ok(version);
+                }
+            }
+        }
+    }
+
+    Err("Python is not installed or not in PATH".to_string())
+}
+
+#[derive(Serialize)]
+pub struct HomebrewInfo {
+    path: String,
+    prefix: String,
+    version: String,
+}
+
+// Check Homebrew presence, prefix and version (macOS and Linuxbrew)
+// Apple Silicon installs to /opt/homebrew, Intel Macs to /usr/local
+#[tauri::command]
+async fn check_homebrew() -> Result<HomebrewInfo, String> {
+    #[cfg(target_os = "windows")]
+    {
+        return Err("Not applicable on this platform".to_string());
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    tauri::async_runtime::spawn_blocking(|| {
+        let candidates = [
+            "/opt/homebrew/bin/brew",
+            "/usr/local/bin/brew",
+            "/home/linuxbrew/.linuxbrew/bin/brew",
+        ];
+
+        // Fall back to anything else on the enhanced PATH (custom prefixes)
+        let brew = candidates
+            .iter()
+            .map(std::path::PathBuf::from)
+            .find(|path| is_executable(path))
+            .or_else(|| find_executable("brew", &get_enhanced_path_entries()))
+            .ok_or_else(|| "Homebrew is not installed".to_string())?;
+
+        // <prefix>/bin/brew
+        let prefix = brew
+            .parent()
+            .and_then(|bin| bin.parent())
+            .map(|prefix| prefix.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let output = std::process::Command::new(&brew)
+            .arg("--version")
+            .env("PATH", get_enhanced_path())
+            .output_with_timeout(VERSION_PROBE_TIMEOUT)
+            .map_err(|e| format!("Failed to run brew: {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "brew --version failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        // First line looks like "Homebrew 4.3.5"
+        let version = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        Ok(HomebrewInfo {
+            path: brew.to_string_lossy().to_string(),
+            prefix,
+            version,
+        })
+    })
+    .await
+    .map_err(|e| format!("Homebrew check failed: {}", e))?
+}
+
+#[derive(Serialize)]
+pub struct XcodeCltInfo {
+    installed: bool,
+    path: Option<String>,
+}
+
+// Check for the Xcode Command Line Tools (macOS only)
+// git and node native modules need them; `xcode-select -p` fails when they are missing
+#[tauri::command]
+async fn check_xcode_clt() -> Result<XcodeCltInfo, String> {
+    #[cfg(not(target_os = "macos"))]
+    {
+        return Err("Not applicable on this platform".to_string());
+    }
+
+    #[cfg(target_os = "macos")]
+    tauri::async_runtime::spawn_blocking(|| -> Result<XcodeCltInfo, String> {
+        let output = std::process::Command::new("xcode-select")
+            .arg("-p")
+            .output_with_timeout(VERSION_PROBE_TIMEOUT)
+            .map_err(|e| format!("Failed to run xcode-select: {}", e))?;
+
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() || path.is_empty() {
+            return Ok(XcodeCltInfo {
+                installed: false,
+                path: None,
+            });
+        }
+
+        // A stale selection can point at a removed Xcode, so check the tools actually exist
+        let installed = std::path::Path::new(&path).join("usr/bin/git").exists();
+        Ok(XcodeCltInfo {
+            installed,
+            path: Some(path),
+        })
+    })
+    .await
+    .map_err(|e| format!("Xcode Command Line Tools check failed: {}", e))?
+}
+
+#[derive(Serialize)]
+pub struct PermissionStatus {
+    name: &'static str,
+    status: &'static str,  // "granted", "denied", "unknown" (not queryable) or "not_checked"
+    settings_url: &'static str,  // Opens the matching System Settings pane
+}
+
+// Report the macOS privacy (TCC) permissions the backend depends on (macOS only)
+// TCC has no public query API, so access is probed directly; the backend is a child of the
+// app, so macOS attributes its accesses to the app and these results apply to it too
+// Reading Desktop/Documents/Downloads shows the consent prompt when access is undecided, so
+// those folders are only probed when `probe_folders` is set from an explicit user action
+// (e.g. a "Check folder access" button); otherwise they are reported as "not_checked"
+#[tauri::command]
+async fn check_macos_permissions(probe_folders: Option<bool>) -> Result<Vec<PermissionStatus>, String> {
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = probe_folders;
+        return Err("Not applicable on this platform".to_string());
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let home = env::var("HOME").map_err(|_| "HOME is not set".to_string())?;
+        let home = std::path::Path::new(&home);
+        let probe_folders = probe_folders.unwrap_or(false);
 
-        if let Ok(output) = output {
-            if output.status.success() {
-                let version = String::from_utf8_lossy(&output.stdout)
-                    .trim()
-                    .to_string();
-                return Ok(version);
+        let probe = |path: std::path::PathBuf| match std::fs::read_dir(&path) {
+            Ok(_) => "granted",
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => "denied",
+            Err(_) => "unknown",
+        };
+        let probe_folder = |path: std::path::PathBuf| {
+            if probe_folders {
+                probe(path)
+            } else {
+                "not_checked"
             }
-        }
+        };
+
+        Ok(vec![
+            // The TCC database itself is only readable with Full Disk Access, which never prompts
+            PermissionStatus {
+                name: "full_disk_access",
+                status: probe(home.join("Library/Application Support/com.apple.TCC")),
+                settings_url: "x-apple.systempreferences:com.apple.preference.security?Privacy_AllFiles",
+            },
+            PermissionStatus {
+                name: "desktop_folder",
+                status: probe_folder(home.join("Desktop")),
+                settings_url: "x-apple.systempreferences:com.apple.preference.security?Privacy_FilesAndFolders",
+            },
+            PermissionStatus {
+                name: "documents_folder",
+                status: probe_folder(home.join("Documents")),
+                settings_url: "x-apple.systempreferences:com.apple.preference.security?Privacy_FilesAndFolders",
+            },
+            PermissionStatus {
+                name: "downloads_folder",
+                status: probe_folder(home.join("Downloads")),
+                settings_url: "x-apple.systempreferences:com.apple.preference.security?Privacy_FilesAndFolders",
+            },
+            // Local network access (macOS 15+) can't be probed without triggering the prompt
+            PermissionStatus {
+                name: "local_network",
+                status: "unknown",
+                settings_url: "x-apple.systempreferences:com.apple.preference.security?Privacy_LocalNetwork",
+            },
+        ])
     }
+}
 
-    Err("Node.js is not installed or not in PATH".to_string())
+// Path of the bundled sidecar binary; Tauri places externalBin next to the app executable
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+fn sidecar_binary_path() -> Result<std::path::PathBuf, String> {
+    let exe = env::current_exe().map_err(|e| format!("Failed to resolve app executable: {}", e))?;
+    let dir = exe
+        .parent()
+        .ok_or_else(|| "App executable has no parent directory".to_string())?;
+    Ok(dir.join(format!("python-backend{}", env::consts::EXE_SUFFIX)))
 }
 
-// Check Git Bash path (Windows only)
-// Returns the path if CLAUDE_CODE_GIT_BASH_PATH is set and the file exists,
-// or tries to auto-detect Git Bash in common locations
+#[derive(Serialize)]
+pub struct SignatureInfo {
+    path: String,
+    signed: bool,
+    valid: bool,  // Signed and the signature verifies against the file contents
+    signer: Option<String>,
+    detail: String,  // Raw verifier status/message
+}
+
+// Authenticode may check the certificate's revocation status online, so allow for a slow network
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const SIGNATURE_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Verify the sidecar's code signature (codesign on macOS, Authenticode on Windows)
+// Helps tell a legitimately bundled sidecar from one that was corrupted or replaced
 #[tauri::command]
-async fn check_git_bash_path() -> Result<String, String> {
-    // Only relevant on Windows
-    #[cfg(not(target_os = "windows"))]
+async fn verify_sidecar_signature() -> Result<SignatureInfo, String> {
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     {
         return Err("Not applicable on this platform".to_string());
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        // First check if CLAUDE_CODE_GIT_BASH_PATH is set
-        if let Ok(git_bash_path) = env::var("CLAUDE_CODE_GIT_BASH_PATH") {
-            if std::path::Path::new(&git_bash_path).exists() {
-                return Ok(git_bash_path);
-            }
-        }
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    let path = sidecar_binary_path()?;
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    if !path.exists() {
+        return Err(format!("Sidecar not found at {}", path.display()));
+    }
 
-        // Try to auto-detect Git Bash in common locations
-        let common_paths = vec![
-            // Default Git for Windows installation paths
-            r"C:\Program Files\Git\bin\bash.exe",
-            r"C:\Program Files (x86)\Git\bin\bash.exe",
-        ];
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    tauri::async_runtime::spawn_blocking(move || -> Result<SignatureInfo, String> {
+        #[cfg(target_os = "macos")]
+        {
+            let verify = std::process::Command::new("codesign")
+                .args(["--verify", "--strict", "--verbose=2"])
+                .arg(&path)
+                .output_with_timeout(SIGNATURE_CHECK_TIMEOUT)
+                .map_err(|e| format!("Failed to run codesign: {}", e))?;
+            let detail = String::from_utf8_lossy(&verify.stderr).trim().to_string();
 
-        // Also check LOCALAPPDATA and ProgramFiles
-        if let Ok(localappdata) = env::var("LOCALAPPDATA") {
-            let path = format!(r"{}\Programs\Git\bin\bash.exe", localappdata);
-            if std::path::Path::new(&path).exists() {
-                return Ok(path);
-            }
-        }
+            // `codesign -dvv` prints the certificate chain as Authority= lines, leaf first
+            let display = std::process::Command::new("codesign")
+                .arg("-dvv")
+                .arg(&path)
+                .output_with_timeout(SIGNATURE_CHECK_TIMEOUT)
+                .map_err(|e| format!("Failed to run codesign: {}", e))?;
+            let display = String::from_utf8_lossy(&display.stderr).to_string();
+            let signed = !display.contains("not signed at all");
+            let signer = display
+                .lines()
+                .find_map(|line| line.strip_prefix("Authority="))
+                .map(|signer| signer.to_string());
 
-        if let Ok(programfiles) = env::var("ProgramFiles") {
-            let path = format!(r"{}\Git\bin\bash.exe", programfiles);
-            if std::path::Path::new(&path).exists() {
-                return Ok(path);
-            }
+            Ok(SignatureInfo {
+                path: path.display().to_string(),
+                signed,
+                valid: signed && verify.status.success(),
+                signer,
+                detail,
+            })
         }
 
-        for path in common_paths {
-            if std::path::Path::new(path).exists() {
-                return Ok(path.to_string());
+        #[cfg(target_os = "windows")]
+        {
+            let script = format!(
+                "$s = Get-AuthenticodeSignature -LiteralPath '{}'; $s.Status; $s.SignerCertificate.Subject",
+                path.display().to_string().replace('\'', "''")
+            );
+            let output = std::process::Command::new("powershell")
+                .args(["-NoProfile", "-Command", &script])
+                .creation_flags(0x08000000) // CREATE_NO_WINDOW
+                .output_with_timeout(SIGNATURE_CHECK_TIMEOUT)
+                .map_err(|e| format!("Failed to run Get-AuthenticodeSignature: {}", e))?;
+            if !output.status.success() {
+                return Err(format!(
+                    "Get-AuthenticodeSignature failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
             }
+
+            // Status is e.g. Valid, NotSigned, HashMismatch, NotTrusted
+            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            let mut lines = stdout.lines().map(str::trim).filter(|line| !line.is_empty());
+            let status = lines.next().unwrap_or_default().to_string();
+            let signer = lines.next().map(|subject| subject.to_string());
+
+            Ok(SignatureInfo {
+                path: path.display().to_string(),
+                signed: status != "NotSigned",
+                valid: status == "Valid",
+                signer,
+                detail: status,
+            })
         }
+    })
+    .await
+    .map_err(|e| format!("Signature check failed: {}", e))?
+}
 
-        Err("Git Bash not found".to_string())
-    }
+// Common shared libraries and the packages that provide them, as (soname prefix, Debian/Ubuntu, Fedora/RHEL)
+#[cfg(target_os = "linux")]
+const LIBRARY_PACKAGES: [(&str, &str, &str); 10] = [
+    ("libssl.so", "libssl3", "openssl-libs"),
+    ("libcrypto.so", "libssl3", "openssl-libs"),
+    ("libstdc++.so", "libstdc++6", "libstdc++"),
+    ("libgcc_s.so", "libgcc-s1", "libgcc"),
+    ("libz.so", "zlib1g", "zlib"),
+    ("libffi.so", "libffi8", "libffi"),
+    ("libbz2.so", "libbz2-1.0", "bzip2-libs"),
+    ("liblzma.so", "liblzma5", "xz-libs"),
+    ("libsqlite3.so", "libsqlite3-0", "sqlite-libs"),
+    ("libuuid.so", "libuuid1", "libuuid"),
+];
+
+#[derive(Serialize)]
+pub struct MissingLibrary {
+    library: String,
+    version: Option<String>,  // Set when the library exists but lacks a required symbol version (e.g. GLIBCXX_3.4.29)
+    package_hint: Option<String>,  // e.g. "libssl3 (Debian/Ubuntu) or openssl-libs (Fedora/RHEL)"
 }
 
-// Check Python version
+#[derive(Serialize)]
+pub struct LibraryReport {
+    path: String,
+    resolved: usize,
+    missing: Vec<MissingLibrary>,
+}
+
+// ldd runs the binary's loader, which a broken or hostile binary can keep from finishing
+#[cfg(target_os = "linux")]
+const LDD_TIMEOUT: Duration = Duration::from_secs(15);
+
+// List the shared libraries the sidecar (or another binary, e.g. a native module it loads)
+// can't resolve, according to ldd (Linux only)
+// Catches the "backend fails to start with a loader error" class and names the package to install
 #[tauri::command]
-async fn check_python_version() -> Result<String, String> {
-    let enhanced_path = get_enhanced_path();
+async fn check_sidecar_libraries(path: Option<String>) -> Result<LibraryReport, String> {
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = path;
+        return Err("Not applicable on this platform".to_string());
+    }
 
-    // Windows uses python.exe, Unix uses python3 or python
-    #[cfg(target_os = "windows")]
-    let python_commands = vec!["python.exe", "python3.exe", "py.exe"];
+    #[cfg(target_os = "linux")]
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = match path {
+            Some(path) => std::path::PathBuf::from(path),
+            None => sidecar_binary_path()?,
+        };
+        if !path.exists() {
+            return Err(format!("Binary not found at {}", path.display()));
+        }
 
-    #[cfg(not(target_os = "windows"))]
-    let python_commands = vec!["python3", "python"];
+        let output = std::process::Command::new("ldd")
+            .arg(&path)
+            .output_with_timeout(LDD_TIMEOUT)
+            .map_err(|e| format!("Failed to run ldd: {}", e))?;
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if !output.status.success() && stdout.trim().is_empty() {
+            return Err(format!("ldd failed: {}", stderr.trim()));
+        }
 
-    // Try each Python command with enhanced PATH
-    for cmd in &python_commands {
-        let output = std::process::Command::new(cmd)
-            .arg("--version")
-            .env("PATH", &enhanced_path)
-            .output();
+        let package_hint = |library: &str| {
+            LIBRARY_PACKAGES
+                .iter()
+                .find(|(prefix, _, _)| library.starts_with(prefix))
+                .map(|(_, debian, fedora)| format!("{} (Debian/Ubuntu) or {} (Fedora/RHEL)", debian, fedora))
+        };
 
-        if let Ok(output) = output {
-            if output.status.success() {
-                // Python 2.x writes version to stderr, Python 3.x to stdout
-                let version_str = if !output.stdout.is_empty() {
-                    String::from_utf8_lossy(&output.stdout)
+        // Missing libraries look like "\tlibfoo.so.1 => not found"; wrong versions like
+        // "<binary>: /lib/.../libstdc++.so.6: version `GLIBCXX_3.4.29' not found (required by ...)"
+        let mut resolved = 0;
+        let mut missing: Vec<MissingLibrary> = Vec::new();
+        for line in stdout.lines().chain(stderr.lines()) {
+            let line = line.trim();
+            if let Some((library, target)) = line.split_once(" => ") {
+                if target.trim() == "not found" {
+                    missing.push(MissingLibrary {
+                        library: library.to_string(),
+                        version: None,
+                        package_hint: package_hint(library),
+                    });
                 } else {
-                    String::from_utf8_lossy(&output.stderr)
+                    resolved += 1;
+                }
+            } else if let Some((prefix, rest)) = line.split_once(": version `") {
+                let Some((version, _)) = rest.split_once("' not found") else {
+                    continue;
                 };
-
-                let version = version_str.trim().to_string();
-                if !version.is_empty() {
-                    return Ok(version);
+                let library = prefix
+                    .rsplit(": ")
+                    .next()
+                    .and_then(|library| std::path::Path::new(library).file_name())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| prefix.to_string());
+                if !missing.iter().any(|m| m.library == library && m.version.as_deref() == Some(version)) {
+                    missing.push(MissingLibrary {
+                        package_hint: package_hint(&library),
+                        library,
+                        version: Some(version.to_string()),
+                    });
                 }
             }
         }
-    }
 
-    // On Unix systems, try using user's shell as fallback (for pyenv, etc.)
-    #[cfg(not(target_os = "windows"))]
-    {
-        let home = env::var("HOME").unwrap_or_default();
-        let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
+        Ok(LibraryReport {
+            path: path.display().to_string(),
+            resolved,
+            missing,
+        })
+    })
+    .await
+    .map_err(|e| format!("Library check failed: {}", e))?
+}
 
-        let output = std::process::Command::new(&shell)
-            .arg("-l")  // Login shell to source profile
-            .arg("-c")  // Execute command
-            .arg("python3 --version 2>&1 || python --version 2>&1")
-            .output();
+// Resolve the OS default handler for http(s) URLs, i.e. where the opener plugin sends links
+// Returns a platform-specific identifier: a bundle id on macOS, a .desktop file on Linux,
+// a ProgId on Windows
+#[tauri::command]
+async fn get_default_browser() -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(|| -> Result<String, String> {
+        #[cfg(target_os = "macos")]
+        {
+            let home = env::var("HOME").unwrap_or_default();
+            let plist = format!(
+                "{}/Library/Preferences/com.apple.LaunchServices/com.apple.launchservices.secure.plist",
+                home
+            );
+            let output = std::process::Command::new("plutil")
+                .args(["-convert", "json", "-o", "-", &plist])
+                .output_with_timeout(VERSION_PROBE_TIMEOUT)
+                .map_err(|e| format!("Failed to run plutil: {}", e))?;
 
-        if let Ok(output) = output {
-            if output.status.success() {
-                let version = String::from_utf8_lossy(&output.stdout)
-                    .trim()
-                    .to_string();
-                if !version.is_empty() {
-                    return Ok(version);
-                }
+            // Without a user override, macOS opens links in Safari
+            if !output.status.success() {
+                return Ok("com.apple.safari".to_string());
             }
+
+            let prefs: serde_json::Value = serde_json::from_slice(&output.stdout)
+                .map_err(|e| format!("Failed to parse LaunchServices preferences: {}", e))?;
+            let handler = prefs
+                .get("LSHandlers")
+                .and_then(|handlers| handlers.as_array())
+                .and_then(|handlers| {
+                    handlers.iter().find_map(|handler| {
+                        let scheme = handler.get("LSHandlerURLScheme")?.as_str()?;
+                        if scheme == "http" || scheme == "https" {
+                            handler.get("LSHandlerRoleAll")?.as_str().map(|s| s.to_string())
+                        } else {
+                            None
+                        }
+                    })
+                });
+
+            Ok(handler.unwrap_or_else(|| "com.apple.safari".to_string()))
         }
 
-        // Try pyenv directly if available
-        let pyenv_path = format!("{}/.pyenv/shims/python3", home);
-        if std::path::Path::new(&pyenv_path).exists() {
-            if let Ok(output) = std::process::Command::new(&pyenv_path)
-                .arg("--version")
-                .output() {
-                if output.status.success() {
-                    let version = String::from_utf8_lossy(&output.stdout)
-                        .trim()
-                        .to_string();
-                    return Ok(version);
-                }
+        #[cfg(target_os = "linux")]
+        {
+            let output = std::process::Command::new("xdg-settings")
+                .args(["get", "default-web-browser"])
+                .output_with_timeout(VERSION_PROBE_TIMEOUT)
+                .map_err(|e| format!("Failed to run xdg-settings: {}", e))?;
+
+            let browser = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !output.status.success() || browser.is_empty() {
+                return Err("No default browser is configured".to_string());
             }
+            Ok(browser)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let output = std::process::Command::new("reg")
+                .args([
+                    "query",
+                    r"HKCU\Software\Microsoft\Windows\Shell\Associations\UrlAssociations\http\UserChoice",
+                    "/v",
+                    "ProgId",
+                ])
+                .creation_flags(0x08000000) // CREATE_NO_WINDOW
+                .output_with_timeout(VERSION_PROBE_TIMEOUT)
+                .map_err(|e| format!("Failed to query the registry: {}", e))?;
+
+            // Output line looks like: "    ProgId    REG_SZ    ChromeHTML"
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find_map(|line| {
+                    let mut fields = line.split_whitespace();
+                    (fields.next() == Some("ProgId") && fields.next() == Some("REG_SZ"))
+                        .then(|| fields.collect::<Vec<_>>().join(" "))
+                })
+                .ok_or_else(|| "No default browser is configured".to_string())
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+        {
+            Err("Not applicable on this platform".to_string())
         }
+    })
+    .await
+    .map_err(|e| format!("Default browser check failed: {}", e))?
+}
+
+#[derive(Serialize)]
+pub struct UmaskInfo {
+    umask: String,  // Octal, e.g. "0022"
+    warning: Option<String>,
+}
+
+// Report the process umask, which the sidecar inherits (Unix only)
+// Launchers that set an owner-restrictive umask can stop the backend writing its own files
+#[tauri::command]
+async fn check_umask() -> Result<UmaskInfo, String> {
+    #[cfg(not(unix))]
+    {
+        return Err("Not applicable on this platform".to_string());
     }
 
-    // On Windows, try PowerShell as fallback
-    #[cfg(target_os = "windows")]
+    #[cfg(unix)]
     {
-        let output = std::process::Command::new("powershell")
-            .args(["-NoProfile", "-Command", "python --version"])
-            .output();
+        // umask can only be read by setting it, so immediately restore the original value
+        let mask = u32::from(unsafe {
+            let mask = libc::umask(0o022);
+            libc::umask(mask);
+            mask
+        });
 
-        if let Ok(output) = output {
-            if output.status.success() {
-                let version_str = if !output.stdout.is_empty() {
-                    String::from_utf8_lossy(&output.stdout)
-                } else {
-                    String::from_utf8_lossy(&output.stderr)
-                };
-                let version = version_str.trim().to_string();
-                if !version.is_empty() {
-                    return Ok(version);
-                }
+        let warning = (mask & 0o700 != 0).then(|| {
+            "umask removes owner permissions; the backend may fail to create or write its files"
+                .to_string()
+        });
+
+        Ok(UmaskInfo {
+            umask: format!("{:04o}", mask),
+            warning,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct BundledFileIssue {
+    path: String,
+    problem: String,  // "missing", "size_mismatch" or "hash_mismatch"
+}
+
+// Whether the directories the enhanced PATH adds differ from the previous launch's, computed
+// once per launch
+type SharedPathChange = Arc<tokio::sync::OnceCell<bool>>;
+
+// Compare the toolchain directories found this launch with those recorded on the previous one
+// The inherited PATH isn't included: it differs between Finder and terminal launches, which
+// would report a change every time the user switched
+async fn detect_path_change(app: &tauri::AppHandle) -> bool {
+    let cell = app.state::<SharedPathChange>().inner().clone();
+    let app = app.clone();
+    *cell
+        .get_or_init(|| async move {
+            tauri::async_runtime::spawn_blocking(move || {
+                use sha2::{Digest, Sha256};
+
+                let path = scan_toolchain_path_entries().0.join(PATH_SEPARATOR);
+                let hash: String = Sha256::digest(path.as_bytes())
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect();
+                snapshot::update_path_hash(&app, &hash).unwrap_or(false)
+            })
+            .await
+            .unwrap_or(false)
+        })
+        .await
+}
+
+// Whether the enhanced PATH changed since the last launch, e.g. because a runtime was
+// installed in between; the UI can then suggest re-running the prerequisite checks
+#[tauri::command]
+async fn has_path_changed(app: tauri::AppHandle) -> Result<bool, String> {
+    Ok(detect_path_change(&app).await)
+}
+
+fn sha256_file(path: &std::path::Path) -> std::io::Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+// Verify bundled resource files against the manifest embedded at build time
+// Returns the missing or corrupt files; an empty list means the install is intact.
+// Corrupt files can't be repaired in place, so the UI should suggest reinstalling.
+#[tauri::command]
+async fn verify_bundled_files(app: tauri::AppHandle) -> Result<Vec<BundledFileIssue>, String> {
+    require_bundled_runtime(&app).await?;
+    let resource_dir = app
+        .path()
+        .resource_dir()
+        .map_err(|e| format!("Failed to resolve resource dir: {}", e))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut issues = Vec::new();
+        // Manifest paths are relative to the resource dir, as laid out by the bundler
+        for (path, size, hash) in bundled::BUNDLED_FILES {
+            let file = resource_dir.join(path);
+            let problem = match file.metadata() {
+                Err(_) => Some("missing"),
+                Ok(meta) if meta.len() != *size => Some("size_mismatch"),
+                Ok(_) => match sha256_file(&file) {
+                    Ok(actual) if actual == *hash => None,
+                    _ => Some("hash_mismatch"),
+                },
+            };
+            if let Some(problem) = problem {
+                issues.push(BundledFileIssue {
+                    path: path.to_string(),
+                    problem: problem.to_string(),
+                });
             }
         }
+        issues
+    })
+    .await
+    .map_err(|e| format!("Failed to verify bundled files: {}", e))
+}
+
+// Log file (in the app log dir, next to backend.log) for our own process-management logs;
+// the log plugin adds the .log extension
+const APP_LOG_FILE_NAME: &str = "owork-app";
+const APP_LOG_MAX_BYTES: u128 = 5 * 1024 * 1024;
+
+// Set to 1 to force verbose app logging regardless of the persisted setting
+const VERBOSE_LOG_ENV: &str = "OWORK_VERBOSE_LOG";
+
+// Log at debug level when verbose logging is enabled (or forced by the env var), else info
+fn apply_log_level(verbose: bool) {
+    let forced = env::var(VERBOSE_LOG_ENV)
+        .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    log::set_max_level(if verbose || forced {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Info
+    });
+}
+
+// Log every command invocation at debug level before dispatching it
+fn log_invocations<R: tauri::Runtime>(
+    handler: impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        log::debug!(target: "owork::commands", "{}", invoke.message.command());
+        handler(invoke)
     }
+}
 
-    Err("Python is not installed or not in PATH".to_string())
+// Enable or disable (and persist) verbose app logging, returning the log file path
+#[tauri::command]
+async fn set_verbose_logging(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    enabled: bool,
+) -> Result<String, String> {
+    {
+        let mut settings = settings.lock().await;
+        let mut updated = settings.clone();
+        updated.verbose_logging = enabled;
+        settings::save(&app, &updated)?;
+        *settings = updated;
+    }
+    apply_log_level(enabled);
+
+    let log_dir = app
+        .path()
+        .app_log_dir()
+        .map_err(|e| format!("Failed to resolve app log dir: {}", e))?;
+    Ok(log_dir
+        .join(format!("{}.log", APP_LOG_FILE_NAME))
+        .display()
+        .to_string())
+}
+
+// Store (or with None, remove) the token forwarded to the backend in the OS keychain;
+// it takes effect on the next backend start
+#[tauri::command]
+async fn set_backend_token(
+    cache: tauri::State<'_, secrets::SharedTokenCache>,
+    token: Option<String>,
+) -> Result<(), String> {
+    let token = token.filter(|token| !token.trim().is_empty());
+    secrets::save_backend_token(token.as_deref())?;
+    cache.set(token);
+    Ok(())
+}
+
+// Whether a backend token is stored; the token itself is never returned to the webview
+#[tauri::command]
+async fn has_backend_token() -> Result<bool, String> {
+    Ok(secrets::load_backend_token()?.is_some())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let mut builder = tauri::Builder::default()
+    let mut builder = tauri::Builder::default();
+
+    // Must be registered first: a second launch hands over to the running instance and exits
+    // before it can start a competing backend
+    #[cfg(desktop)]
+    {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            let _ = app.emit("second-instance-launched", ());
+        }));
+    }
+
+    builder = builder
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_notification::init());
+        .plugin(tauri_plugin_notification::init())
+        .plugin(
+            tauri_plugin_log::Builder::new()
+                .clear_targets()
+                .target(tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::LogDir {
+                    file_name: Some(APP_LOG_FILE_NAME.to_string()),
+                }))
+                .target(tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Stdout))
+                .max_file_size(APP_LOG_MAX_BYTES)
+                .rotation_strategy(tauri_plugin_log::RotationStrategy::KeepOne)
+                // The most verbose level we ever log at; apply_log_level picks the active one
+                .level(log::LevelFilter::Debug)
+                .level_for("reqwest", log::LevelFilter::Warn)
+                .level_for("hyper_util", log::LevelFilter::Warn)
+                .level_for("tao", log::LevelFilter::Warn)
+                .build(),
+        );
 
     // Add desktop-only plugins
     #[cfg(desktop)]
@@ -590,18 +6636,201 @@ pub fn run() {
 
     builder
         .manage(Arc::new(Mutex::new(BackendState::default())))
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(log_invocations(tauri::generate_handler![
             start_backend,
             stop_backend,
             get_backend_status,
+            get_detailed_status,
+            is_first_run,
             get_backend_port,
             check_nodejs_version,
             check_python_version,
+            warm_version_checks,
+            get_cached_version_checks,
+            has_path_changed,
             check_git_bash_path,
-        ])
+            get_health_path,
+            set_health_path,
+            ping_backend,
+            set_auto_restart,
+            get_enhanced_path_list,
+            check_homebrew,
+            get_backend_logs,
+            get_recent_stderr,
+            export_filtered_backend_logs,
+            set_log_limits,
+            which_on_enhanced_path,
+            get_app_version,
+            clear_backend_logs,
+            get_python_env,
+            benchmark_backend,
+            set_shutdown_endpoint,
+            check_umask,
+            list_backend_children,
+            set_crash_reports_enabled,
+            set_sidecar_args,
+            verify_bundled_files,
+            prepare_backend,
+            commit_backend_start,
+            get_sidecar_environment,
+            set_auto_start_backend,
+            check_xcode_clt,
+            set_login_shell_fallback,
+            get_diagnostics,
+            measure_path_scan,
+            set_health_expected_status,
+            set_health_endpoint,
+            get_default_browser,
+            set_watchdog_interval,
+            set_resource_limits,
+            recover_previous_backend,
+            run_backend_command,
+            prepare_backend_dependencies,
+            cancel_backend_dependencies,
+            run_backend_self_test,
+            set_backend_command_limit,
+            verify_sidecar_signature,
+            check_sidecar_libraries,
+            set_readiness_timeouts,
+            rotate_backend_log,
+            get_emulation_info,
+            start_backend_streaming,
+            set_version_check_timeout,
+            get_backend_data_dir,
+            set_backend_data_dir,
+            list_python_interpreters,
+            validate_runtime_path,
+            check_pyenv_shims,
+            list_global_packages,
+            restart_backend,
+            nuke_backend,
+            check_backend_after_wake,
+            get_backend_settings,
+            stream_backend_resource_usage,
+            set_backend_settings,
+            reset_backend_settings_to_default,
+            set_restart_on_wake,
+            set_python_runtime,
+            register_shutdown_hook,
+            unregister_shutdown_hook,
+            acknowledge_shutdown,
+            get_backend_bind_info,
+            set_preferred_port,
+            is_another_instance_running,
+            check_prerequisites,
+            get_minimal_path,
+            set_minimal_path,
+            get_backend_env_report,
+            set_devtools_on_fatal,
+            set_keep_backend_alive_on_close,
+            get_fd_limits,
+            set_raise_fd_limit,
+            get_ipc_stats,
+            get_output_decoding_stats,
+            set_log_batching,
+            set_strip_ansi,
+            get_max_body_size,
+            set_max_body_size,
+            set_backend_priority,
+            check_port_ownership,
+            set_platform_startup_timeouts,
+            set_first_launch_startup_timeout,
+            get_backend_capabilities,
+            get_backend_concurrency,
+            set_backend_concurrency,
+            check_clock_skew,
+            check_ports_available,
+            set_first_run_startup_timeout,
+            check_macos_permissions,
+            set_verbose_logging,
+            set_backend_token,
+            has_backend_token,
+            check_webview_reachability,
+            get_startup_timing,
+            check_stale_lock_files,
+            check_filesystem_case_sensitivity,
+        ]))
         .setup(|app| {
-            // Backend will be started by frontend via initializeBackend()
-            // This allows proper error handling in the UI
+            // Load persisted backend settings
+            let backend_settings = settings::load(app.handle());
+            #[cfg(debug_assertions)]
+            let watch_dir = backend_settings
+                .backend_source_dir
+                .clone()
+                .filter(|_| backend_settings.python_runtime == settings::PythonRuntime::System);
+            apply_log_level(backend_settings.verbose_logging);
+
+            // Keep recent backend output in memory and persist it to the app log dir
+            let mut log_buffer = logs::LogBuffer::new(backend_settings.log_buffer_capacity);
+            match app.path().app_log_dir() {
+                Ok(log_dir) => {
+                    if let Err(e) = log_buffer.attach_file(log_dir) {
+                        log::warn!("Backend logs will not be persisted: {}", e);
+                    }
+                }
+                Err(e) => log::warn!("Failed to resolve app log dir: {}", e),
+            }
+            app.manage::<SharedLogBuffer>(Arc::new(Mutex::new(log_buffer)));
+            app.manage::<logs::SharedEventStats>(Arc::new(logs::EventStats::new()));
+            app.manage::<logs::SharedDecodeStats>(Arc::new(logs::DecodeStats::default()));
+            app.manage::<SharedDependencyJob>(Arc::new(Mutex::new(None)));
+            app.manage::<SharedShutdownHooks>(Arc::new(ShutdownHooks::default()));
+            app.manage::<SharedVersionCache>(Arc::new(VersionCache::default()));
+            app.manage::<SharedPathChange>(Arc::new(tokio::sync::OnceCell::new()));
+            app.manage::<SharedSidecarHelp>(Arc::new(tokio::sync::OnceCell::new()));
+            app.manage::<secrets::SharedTokenCache>(Arc::new(secrets::TokenCache::default()));
+            let auto_start_backend = backend_settings.auto_start_backend;
+            let keep_backend_alive = backend_settings.keep_backend_alive_on_close;
+            app.manage::<SharedCommandLimiter>(Arc::new(Mutex::new(CommandLimiter::new(
+                backend_settings.max_concurrent_backend_commands.max(1),
+            ))));
+            app.manage::<SharedSettings>(Arc::new(Mutex::new(backend_settings)));
+
+            spawn_watchdog(app.handle().clone());
+            spawn_wake_detector(app.handle().clone());
+
+            // Record this launch's PATH hash right away, whether or not the UI asks
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                detect_path_change(&handle).await;
+            });
+
+            // Restart the backend when its sources change (debug builds running the system
+            // runtime only; the sidecar can't pick up source changes)
+            #[cfg(debug_assertions)]
+            if let Some(dir) = watch_dir {
+                if let Err(e) = watcher::start(app.handle().clone(), dir.into()) {
+                    log::warn!("Backend source watcher not started: {}", e);
+                }
+            }
+
+            // By default the backend is started by the frontend via initializeBackend(),
+            // which allows proper error handling in the UI. When auto-start is enabled we
+            // start it right away and report the outcome through events instead.
+            if auto_start_backend || keep_backend_alive {
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    // Reattach to the backend left running when the window last closed
+                    if keep_backend_alive {
+                        if let Ok(Some(_)) = recover_backend(&app_handle).await {
+                            return;
+                        }
+                    }
+                    if !auto_start_backend {
+                        return;
+                    }
+
+                    let state = app_handle.state::<SharedBackendState>().inner().clone();
+                    match spawn_backend(app_handle.clone(), state).await {
+                        Ok(port) => {
+                            let _ = app_handle.emit("backend-auto-started", port);
+                        }
+                        Err(e) => {
+                            let _ = app_handle.emit("backend-auto-start-failed", e.to_string());
+                        }
+                    }
+                });
+            }
 
             // Open DevTools automatically in debug builds or when OWORK_DEBUG is set
             #[cfg(debug_assertions)]
@@ -625,23 +6854,72 @@ pub fn run() {
             if let Some(window) = app.get_webview_window("main") {
                 let app_handle = app.handle().clone();
                 window.on_window_event(move |event| {
+                    if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                        // Give a registered frontend its grace window before the teardown below
+                        if let Some(grace) = app_handle.state::<SharedShutdownHooks>().begin() {
+                            api.prevent_close();
+                            let app_handle = app_handle.clone();
+                            tauri::async_runtime::spawn(async move {
+                                wait_for_shutdown_ack(&app_handle, grace).await;
+                                if let Some(window) = app_handle.get_webview_window("main") {
+                                    let _ = window.destroy();
+                                }
+                            });
+                            return;
+                        }
+                    }
+
                     if let tauri::WindowEvent::Destroyed = event {
                         // Clean up backend process when window is destroyed
                         let state = app_handle.state::<SharedBackendState>();
                         let state_clone = state.inner().clone();
+                        let settings = app_handle.state::<SharedSettings>().inner().clone();
 
                         tauri::async_runtime::block_on(async {
+                            let keep_alive = settings.lock().await.keep_backend_alive_on_close;
                             let mut backend = state_clone.lock().await;
 
+                            // Detach instead: dropping the child handle doesn't kill the process,
+                            // and the snapshot lets the next launch reattach to it. Forgetting the
+                            // pid also keeps the exit handlers from killing it
+                            if keep_alive {
+                                // Its output pipes close with us, so the backend first has to move
+                                // its output to its own log file; one that can't, or doesn't
+                                // acknowledge in time, is stopped below instead
+                                let detachable = backend.detachable;
+                                let detached = backend.detached.clone();
+                                let can_detach = match backend.child.as_mut() {
+                                    // Recovered backends aren't attached to our pipes
+                                    None => true,
+                                    Some(child) => {
+                                        detachable
+                                            && child.write(DETACH_COMMAND).is_ok()
+                                            && tokio::time::timeout(DETACH_WAIT, detached.notified()).await.is_ok()
+                                    }
+                                };
+                                if can_detach {
+                                    backend.child = None;
+                                    backend.running = false;
+                                    backend.pid = None;
+                                    return;
+                                }
+                                log::warn!("Backend can't detach from the app; stopping it instead");
+                            }
+
                             // On Windows, use taskkill to kill the entire process tree
                             #[cfg(target_os = "windows")]
                             if let Some(pid) = backend.pid {
-                                kill_process_tree(pid);
-                                println!("Killed backend process tree (PID: {}) on window destroy", pid);
+                                kill_process_tree(&app_handle, pid, "on window destroy");
                             }
 
-                            if let Some(child) = backend.child.take() {
-                                let _ = child.kill();
+                            let killed = match backend.child.take() {
+                                Some(child) => child.kill().is_ok(),
+                                // Recovered backends have no child handle
+                                None => backend.pid.map(process::kill).unwrap_or(false),
+                            };
+                            // Keep the snapshot if the backend may have survived, so the next launch can recover it
+                            if killed {
+                                snapshot::clear(&app_handle);
                             }
                             backend.running = false;
                             backend.pid = None;
@@ -668,21 +6946,33 @@ pub fn run() {
                         // On Windows, use taskkill to kill the entire process tree
                         #[cfg(target_os = "windows")]
                         if let Some(pid) = backend.pid {
-                            kill_process_tree(pid);
-                            println!("Killed backend process tree (PID: {}) on exit", pid);
+                            kill_process_tree(app_handle, pid, "on exit");
                         }
 
-                        if let Some(child) = backend.child.take() {
-                            let _ = child.kill();
-                            println!("Backend process terminated on exit");
+                        let killed = match backend.child.take() {
+                            Some(child) => child.kill().is_ok(),
+                            None => backend.pid.map(process::kill).unwrap_or(false),
+                        };
+                        if killed {
+                            snapshot::clear(app_handle);
+                            cleanup_log(app_handle, log::Level::Info, "Backend process terminated on exit".to_string());
                         }
                         backend.running = false;
                         backend.pid = None;
                     });
                 }
-                tauri::RunEvent::ExitRequested { api, .. } => {
-                    // Don't prevent exit, but ensure cleanup
-                    let _ = api; // Allow default exit behavior
+                tauri::RunEvent::ExitRequested { api, code, .. } => {
+                    // An explicit app.exit() (e.g. from the updater) also gives the frontend its
+                    // grace window; closing the last window was already handled in CloseRequested
+                    if let Some(grace) = code.and_then(|_| app_handle.state::<SharedShutdownHooks>().begin()) {
+                        api.prevent_exit();
+                        let app_handle = app_handle.clone();
+                        tauri::async_runtime::spawn(async move {
+                            wait_for_shutdown_ack(&app_handle, grace).await;
+                            app_handle.exit(code.unwrap_or(0));
+                        });
+                        return;
+                    }
 
                     // Clean up backend process
                     let state = app_handle.state::<SharedBackendState>();
@@ -694,12 +6984,15 @@ pub fn run() {
                         // On Windows, use taskkill to kill the entire process tree
                         #[cfg(target_os = "windows")]
                         if let Some(pid) = backend.pid {
-                            kill_process_tree(pid);
-                            println!("Killed backend process tree (PID: {}) on exit request", pid);
+                            kill_process_tree(app_handle, pid, "on exit request");
                         }
 
-                        if let Some(child) = backend.child.take() {
-                            let _ = child.kill();
+                        let killed = match backend.child.take() {
+                            Some(child) => child.kill().is_ok(),
+                            None => backend.pid.map(process::kill).unwrap_or(false),
+                        };
+                        if killed {
+                            snapshot::clear(app_handle);
                         }
                         backend.running = false;
                         backend.pid = None;
@@ -709,3 +7002,72 @@ pub fn run() {
             }
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(checks: &[SelfTestCheck]) -> Vec<(&str, bool, Option<&str>)> {
+        checks
+            .iter()
+            .map(|check| (check.name.as_str(), check.passed, check.message.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn self_test_parses_a_passing_report() {
+        let stdout = r#"{"passed": true, "checks": [{"name": "database", "passed": true}, {"name": "models", "ok": true, "detail": "3 loaded"}]}"#;
+        let (passed, checks) = parse_self_test_output(stdout).unwrap();
+        assert_eq!(passed, Some(true));
+        assert_eq!(
+            summary(&checks),
+            vec![("database", true, None), ("models", true, Some("3 loaded"))]
+        );
+    }
+
+    #[test]
+    fn self_test_parses_a_failing_report_after_log_lines() {
+        let stdout = "INFO:     Loading settings\n\
+                      WARNING:  No GPU found\n\
+                      {\"ok\": false, \"checks\": [{\"name\": \"ffmpeg\", \"passed\": false, \"message\": \"not on PATH\"}]}\n";
+        let (passed, checks) = parse_self_test_output(stdout).unwrap();
+        assert_eq!(passed, Some(false));
+        assert_eq!(summary(&checks), vec![("ffmpeg", false, Some("not on PATH"))]);
+    }
+
+    #[test]
+    fn self_test_parses_a_bare_array_of_checks() {
+        let stdout = "[{\"name\": \"disk\", \"passed\": true}, {\"name\": \"network\", \"passed\": false}]";
+        let (passed, checks) = parse_self_test_output(stdout).unwrap();
+        assert_eq!(passed, None);
+        assert_eq!(summary(&checks), vec![("disk", true, None), ("network", false, None)]);
+    }
+
+    #[test]
+    fn self_test_parses_a_pretty_printed_report() {
+        let stdout = "{\n  \"passed\": true,\n  \"checks\": []\n}\n";
+        let (passed, checks) = parse_self_test_output(stdout).unwrap();
+        assert_eq!(passed, Some(true));
+        assert!(checks.is_empty());
+    }
+
+    #[test]
+    fn self_test_uses_the_last_line_that_parses() {
+        let stdout = "[{\"name\": \"stale\", \"passed\": false}]\n[{\"name\": \"final\", \"passed\": true}]\n";
+        let (_, checks) = parse_self_test_output(stdout).unwrap();
+        assert_eq!(summary(&checks), vec![("final", true, None)]);
+    }
+
+    #[test]
+    fn self_test_rejects_malformed_output() {
+        // Plain text from older backends
+        assert!(parse_self_test_output("All checks passed\n").is_none());
+        assert!(parse_self_test_output("").is_none());
+        // Truncated JSON
+        assert!(parse_self_test_output("{\"passed\": true, \"checks\": [{\"name\": \"db\"").is_none());
+        // Valid JSON that isn't a report: no checks, or checks missing required fields
+        assert!(parse_self_test_output("{\"passed\": true}").is_none());
+        assert!(parse_self_test_output("[{\"name\": \"db\"}]").is_none());
+        assert!(parse_self_test_output("{\"status\": \"ok\"}\n").is_none());
+    }
+}
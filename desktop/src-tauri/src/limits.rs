@@ -0,0 +1,600 @@
+// Resource limits for the backend process tree
+// Linux: cgroups v2, which needs the app's cgroup delegated to the user
+// Windows: a Job Object with memory and CPU rate caps
+
+#[derive(Clone, Copy)]
+pub struct ResourceLimits {
+    pub memory_limit_mb: Option<u64>,
+    pub cpu_limit_percent: Option<u32>, // Of total CPU capacity, 1-100
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self.memory_limit_mb.is_none() && self.cpu_limit_percent.is_none()
+    }
+}
+
+// Bytes in a memory limit given in MB, rejecting values that overflow
+fn memory_limit_bytes(memory_mb: u64) -> Result<u64, String> {
+    memory_mb
+        .checked_mul(1024 * 1024)
+        .ok_or_else(|| format!("Memory limit is too large: {} MB", memory_mb))
+}
+
+// The container the backend's process tree is confined to: a cgroup on Linux, a job object
+// on Windows. Created with the limits before spawning; the backend and everything it has
+// started are then added with add_tree, and processes they start later inherit it
+// Dropping it removes the (by then empty) cgroup / closes our job handle
+pub struct Confinement {
+    #[cfg(target_os = "linux")]
+    cgroup: std::path::PathBuf,
+    #[cfg(target_os = "windows")]
+    job: usize, // HANDLE, kept as an integer so the confinement can move between threads
+}
+
+// Prefix of the cgroups we create below our own
+#[cfg(target_os = "linux")]
+const CGROUP_PREFIX: &str = "owork-backend-";
+
+// Leaf cgroup the app itself moves into, since cgroups v2 only enables controllers for the
+// children of a cgroup that holds no processes itself
+#[cfg(target_os = "linux")]
+const APP_CGROUP: &str = "owork-app";
+
+// Create a cgroup below our own with memory.max / cpu.max set, enabling the memory and cpu
+// controllers for our subtree first; needs cgroups v2 with our cgroup delegated to the user
+#[cfg(target_os = "linux")]
+pub fn prepare(limits: &ResourceLimits) -> Result<Option<Confinement>, String> {
+    use std::path::Path;
+
+    if limits.is_empty() {
+        return Ok(None);
+    }
+
+    let own = std::fs::read_to_string("/proc/self/cgroup")
+        .map_err(|e| format!("Failed to read /proc/self/cgroup: {}", e))?;
+    // cgroups v2 has a single "0::<path>" entry
+    let own_path = own
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .ok_or_else(|| "cgroups v2 is not available".to_string())?;
+    let own_dir = Path::new("/sys/fs/cgroup").join(own_path.trim_start_matches('/'));
+
+    // After the first backend we already live in the leaf, so work from its parent
+    let parent = if own_dir.file_name().is_some_and(|name| name == APP_CGROUP) {
+        own_dir.parent().map(Path::to_path_buf).unwrap_or(own_dir)
+    } else {
+        own_dir
+    };
+    let write = |path: &Path, value: &str| {
+        std::fs::write(path, value).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    };
+
+    // Groups left behind by a backend that outlived us are removable once empty
+    if let Ok(entries) = std::fs::read_dir(&parent) {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with(CGROUP_PREFIX) {
+                let _ = std::fs::remove_dir(entry.path());
+            }
+        }
+    }
+
+    let mut controllers = Vec::new();
+    if limits.memory_limit_mb.is_some() {
+        controllers.push("memory");
+    }
+    if limits.cpu_limit_percent.is_some() {
+        controllers.push("cpu");
+    }
+    let available = std::fs::read_to_string(parent.join("cgroup.controllers"))
+        .map_err(|e| format!("Failed to read cgroup controllers: {}", e))?;
+    if let Some(missing) = controllers
+        .iter()
+        .find(|controller| !available.split_whitespace().any(|name| name == **controller))
+    {
+        return Err(format!("The {} cgroup controller is not delegated to this user", missing));
+    }
+
+    let enabled = std::fs::read_to_string(parent.join("cgroup.subtree_control")).unwrap_or_default();
+    let to_enable: Vec<String> = controllers
+        .iter()
+        .filter(|controller| !enabled.split_whitespace().any(|name| name == **controller))
+        .map(|controller| format!("+{}", controller))
+        .collect();
+    if !to_enable.is_empty() {
+        let app_group = parent.join(APP_CGROUP);
+        std::fs::create_dir_all(&app_group)
+            .map_err(|e| format!("Failed to create cgroup {}: {}", app_group.display(), e))?;
+        write(&app_group.join("cgroup.procs"), &std::process::id().to_string())?;
+        write(&parent.join("cgroup.subtree_control"), &to_enable.join(" "))?;
+    }
+
+    let cgroup = parent.join(format!("{}{}", CGROUP_PREFIX, crate::logs::now_millis()));
+    std::fs::create_dir_all(&cgroup)
+        .map_err(|e| format!("Failed to create cgroup {}: {}", cgroup.display(), e))?;
+    // From here on, dropping the confinement removes the cgroup again on failure
+    let confinement = Confinement { cgroup };
+    let group = &confinement.cgroup;
+
+    if let Some(memory_mb) = limits.memory_limit_mb {
+        write(&group.join("memory.max"), &memory_limit_bytes(memory_mb)?.to_string())?;
+    }
+    if let Some(percent) = limits.cpu_limit_percent {
+        // cpu.max is "<quota> <period>" in microseconds, relative to a single CPU
+        let period: u64 = 100_000;
+        let cpus = std::thread::available_parallelism().map(|n| n.get() as u64).unwrap_or(1);
+        let quota = (period * cpus * u64::from(percent) / 100).max(1000);
+        write(&group.join("cpu.max"), &format!("{} {}", quota, period))?;
+    }
+    Ok(Some(confinement))
+}
+
+#[cfg(target_os = "linux")]
+impl Confinement {
+    // Move the process and its current descendants into the cgroup
+    // The parent goes first, so anything it forks while we list its children is born inside
+    pub fn add_tree(&self, pid: u32) -> Result<(), String> {
+        let procs = self.cgroup.join("cgroup.procs");
+        let add = |pid: u32| {
+            std::fs::write(&procs, pid.to_string())
+                .map_err(|e| format!("Failed to move pid {} into {}: {}", pid, self.cgroup.display(), e))
+        };
+        add(pid)?;
+        for process in crate::process::descendants(pid)? {
+            // A descendant may already have exited
+            if crate::process::is_process_alive(process.pid) {
+                add(process.pid)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Confinement {
+    fn drop(&mut self) {
+        // Only succeeds once the cgroup is empty; a leftover is cleaned up by the next prepare
+        let _ = std::fs::remove_dir(&self.cgroup);
+    }
+}
+
+// Create a job object with the memory and CPU rate caps
+#[cfg(target_os = "windows")]
+pub fn prepare(limits: &ResourceLimits) -> Result<Option<Confinement>, String> {
+    use windows_sys::Win32::System::JobObjects::*;
+
+    if limits.is_empty() {
+        return Ok(None);
+    }
+
+    unsafe {
+        let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+        if job.is_null() {
+            return Err(format!("Failed to create job object: {}", std::io::Error::last_os_error()));
+        }
+        // Dropping the confinement closes the handle on failure
+        let confinement = Confinement { job: job as usize };
+
+        if let Some(memory_mb) = limits.memory_limit_mb {
+            let bytes = usize::try_from(memory_limit_bytes(memory_mb)?)
+                .map_err(|_| format!("Memory limit is too large: {} MB", memory_mb))?;
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            // Caps the job's processes together, like memory.max does for a cgroup
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_JOB_MEMORY;
+            info.JobMemoryLimit = bytes;
+            if SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &info as *const _ as *const std::ffi::c_void,
+                std::mem::size_of_val(&info) as u32,
+            ) == 0
+            {
+                return Err(format!("Failed to set memory limit: {}", std::io::Error::last_os_error()));
+            }
+        }
+
+        if let Some(percent) = limits.cpu_limit_percent {
+            let mut info: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION = std::mem::zeroed();
+            info.ControlFlags = JOB_OBJECT_CPU_RATE_CONTROL_ENABLE | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP;
+            // CpuRate is in 1/100ths of a percent
+            info.Anonymous.CpuRate = percent * 100;
+            if SetInformationJobObject(
+                job,
+                JobObjectCpuRateControlInformation,
+                &info as *const _ as *const std::ffi::c_void,
+                std::mem::size_of_val(&info) as u32,
+            ) == 0
+            {
+                return Err(format!("Failed to set CPU limit: {}", std::io::Error::last_os_error()));
+            }
+        }
+
+        Ok(Some(confinement))
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Confinement {
+    // Assign the process and its current descendants to the job
+    pub fn add_tree(&self, pid: u32) -> Result<(), String> {
+        use windows_sys::Win32::Foundation::CloseHandle;
+        use windows_sys::Win32::System::JobObjects::AssignProcessToJobObject;
+        use windows_sys::Win32::System::Threading::{OpenProcess, PROCESS_SET_QUOTA, PROCESS_TERMINATE};
+
+        let assign = |pid: u32| unsafe {
+            let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, pid);
+            if process.is_null() {
+                return Err(format!("Failed to open process {}: {}", pid, std::io::Error::last_os_error()));
+            }
+            let assigned = AssignProcessToJobObject(self.job as _, process);
+            CloseHandle(process);
+            if assigned == 0 {
+                return Err(format!(
+                    "Failed to assign process {} to job object: {}",
+                    pid,
+                    std::io::Error::last_os_error()
+                ));
+            }
+            Ok(())
+        };
+        assign(pid)?;
+        for process in crate::process::descendants(pid)? {
+            if crate::process::is_process_alive(process.pid) {
+                assign(process.pid)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for Confinement {
+    fn drop(&mut self) {
+        // The job lives on as long as processes are assigned to it
+        unsafe {
+            windows_sys::Win32::Foundation::CloseHandle(self.job as _);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn prepare(limits: &ResourceLimits) -> Result<Option<Confinement>, String> {
+    if limits.is_empty() {
+        Ok(None)
+    } else {
+        Err("Resource limits are not supported on this platform".to_string())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+impl Confinement {
+    pub fn add_tree(&self, _pid: u32) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// Niceness range we accept: lowering priority only, since raising it needs elevated rights
+pub const MAX_NICE: i32 = 19;
+
+// Set the scheduling priority of a process and all its descendants (the tracked pid is often
+// a launcher whose child does the work); descendants that exit meanwhile are skipped
+pub fn set_priority(pid: u32, nice: i32) -> Result<(), String> {
+    set_process_priority(pid, nice)?;
+    for process in crate::process::descendants(pid)? {
+        if let Err(e) = set_process_priority(process.pid, nice) {
+            if crate::process::is_process_alive(process.pid) {
+                return Err(e);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Set the scheduling priority of one process from a Unix niceness (0 = normal, 19 = lowest)
+// On Linux niceness is per thread, so every thread of the process is reniced
+#[cfg(unix)]
+fn set_process_priority(pid: u32, nice: i32) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    let ids: Vec<u32> = std::fs::read_dir(format!("/proc/{}/task", pid))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+                .collect()
+        })
+        .unwrap_or_else(|_| vec![pid]);
+    #[cfg(not(target_os = "linux"))]
+    let ids = vec![pid];
+
+    for id in ids {
+        let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, id as libc::id_t, nice) };
+        if result != 0 {
+            return Err(format!("Failed to set priority: {}", std::io::Error::last_os_error()));
+        }
+    }
+    Ok(())
+}
+
+// Windows has priority classes instead of niceness: 1-9 map to below normal, 10-19 to idle
+#[cfg(target_os = "windows")]
+fn set_process_priority(pid: u32, nice: i32) -> Result<(), String> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, SetPriorityClass, BELOW_NORMAL_PRIORITY_CLASS, IDLE_PRIORITY_CLASS,
+        NORMAL_PRIORITY_CLASS, PROCESS_SET_INFORMATION,
+    };
+
+    let class = match nice {
+        i32::MIN..=0 => NORMAL_PRIORITY_CLASS,
+        1..=9 => BELOW_NORMAL_PRIORITY_CLASS,
+        _ => IDLE_PRIORITY_CLASS,
+    };
+
+    unsafe {
+        let process = OpenProcess(PROCESS_SET_INFORMATION, 0, pid);
+        if process.is_null() {
+            return Err(format!("Failed to open backend process: {}", std::io::Error::last_os_error()));
+        }
+        let ok = SetPriorityClass(process, class);
+        CloseHandle(process);
+        if ok == 0 {
+            return Err(format!("Failed to set priority: {}", std::io::Error::last_os_error()));
+        }
+    }
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+pub struct FdLimits {
+    soft: Option<u64>,  // None = unlimited, or no such limit on this platform
+    hard: Option<u64>,
+    open: Option<u64>,  // Descriptors/handles the backend holds right now, where measurable
+}
+
+#[cfg(unix)]
+fn nofile_limit() -> Result<libc::rlimit, String> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return Err(format!("Failed to read fd limit: {}", std::io::Error::last_os_error()));
+    }
+    Ok(limit)
+}
+
+#[cfg(unix)]
+fn rlim_value(value: libc::rlim_t) -> Option<u64> {
+    (value != libc::RLIM_INFINITY).then_some(value as u64)
+}
+
+// Our own fd limits, which a backend spawned from now on inherits
+#[cfg(unix)]
+fn own_fd_limits(pid: Option<u32>) -> Result<FdLimits, String> {
+    let limit = nofile_limit()?;
+    Ok(FdLimits {
+        soft: rlim_value(limit.rlim_cur),
+        hard: rlim_value(limit.rlim_max),
+        open: pid.and_then(open_fd_count),
+    })
+}
+
+// The running backend's own fd limits (they may differ from ours if raise_fd_limit was
+// toggled after it started), or ours when no backend is running
+#[cfg(target_os = "linux")]
+pub fn fd_limits(pid: Option<u32>) -> Result<FdLimits, String> {
+    const MAX_OPEN_FILES: &str = "Max open files";
+
+    let Some(pid) = pid else {
+        return own_fd_limits(None);
+    };
+    let limits = std::fs::read_to_string(format!("/proc/{}/limits", pid))
+        .map_err(|e| format!("Failed to read backend limits: {}", e))?;
+    let line = limits
+        .lines()
+        .find(|line| line.starts_with(MAX_OPEN_FILES))
+        .ok_or_else(|| "Backend limits have no open files entry".to_string())?;
+
+    // "Max open files            1024                 524288               files"
+    let mut values = line[MAX_OPEN_FILES.len()..].split_whitespace();
+    let mut next_value = || match values.next() {
+        Some("unlimited") => Ok(None),
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("Invalid open files limit {:?}", value)),
+        None => Err("Backend limits have a truncated open files entry".to_string()),
+    };
+    Ok(FdLimits {
+        soft: next_value()?,
+        hard: next_value()?,
+        open: open_fd_count(pid),
+    })
+}
+
+// Another process's limits can't be read here, so report ours: the backend inherited them when
+// it was spawned, unless raise_fd_limit has changed them since
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn fd_limits(pid: Option<u32>) -> Result<FdLimits, String> {
+    own_fd_limits(pid)
+}
+
+#[cfg(target_os = "linux")]
+fn open_fd_count(pid: u32) -> Option<u64> {
+    std::fs::read_dir(format!("/proc/{}/fd", pid))
+        .ok()
+        .map(|entries| entries.count() as u64)
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn open_fd_count(_pid: u32) -> Option<u64> {
+    None
+}
+
+// Windows has no practical per-process handle limit, so only report the backend's handle count
+#[cfg(target_os = "windows")]
+pub fn fd_limits(pid: Option<u32>) -> Result<FdLimits, String> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{
+        GetProcessHandleCount, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    let open = pid.and_then(|pid| unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return None;
+        }
+        let mut count = 0u32;
+        let ok = GetProcessHandleCount(process, &mut count);
+        CloseHandle(process);
+        (ok != 0).then_some(u64::from(count))
+    });
+    Ok(FdLimits {
+        soft: None,
+        hard: None,
+        open,
+    })
+}
+
+// Raise our soft fd limit up to the hard limit so the next spawned backend inherits it
+// This is the app's process-wide limit: a backend that is already running keeps the one it
+// started with, every process we spawn later inherits the raised one, and it is never lowered
+// again while the app runs. Returns the resulting soft limit (u64::MAX = unlimited)
+#[cfg(unix)]
+pub fn raise_fd_limit() -> Result<u64, String> {
+    let mut limit = nofile_limit()?;
+    // macOS rejects soft limits above OPEN_MAX even when the hard limit is unlimited
+    #[cfg(target_os = "macos")]
+    let target = limit.rlim_max.min(10240);
+    #[cfg(not(target_os = "macos"))]
+    let target = limit.rlim_max;
+
+    if limit.rlim_cur < target {
+        limit.rlim_cur = target;
+        if unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &limit) } != 0 {
+            return Err(format!("Failed to raise fd limit: {}", std::io::Error::last_os_error()));
+        }
+    }
+    Ok(rlim_value(limit.rlim_cur).unwrap_or(u64::MAX))
+}
+
+#[cfg(target_os = "windows")]
+pub fn raise_fd_limit() -> Result<u64, String> {
+    Err("Not applicable on this platform".to_string())
+}
+
+// Cumulative CPU time and current resident memory of a process tree
+pub struct ProcessUsage {
+    pub cpu_time: std::time::Duration,
+    pub rss_bytes: u64,
+}
+
+// Usage of the process and all its descendants: the tracked pid is often a launcher (the
+// onefile bootloader) whose child does the actual work
+// Descendants that exit between listing and sampling are skipped
+pub fn process_usage(pid: u32) -> Result<ProcessUsage, String> {
+    let mut total = single_process_usage(pid)?;
+    for process in crate::process::descendants(pid)? {
+        if let Ok(usage) = single_process_usage(process.pid) {
+            total.cpu_time += usage.cpu_time;
+            total.rss_bytes += usage.rss_bytes;
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(target_os = "linux")]
+fn single_process_usage(pid: u32) -> Result<ProcessUsage, String> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid))
+        .map_err(|e| format!("Failed to read process stats: {}", e))?;
+    // The command name in parentheses may contain spaces, so only split what follows it;
+    // utime, stime and rss (fields 14, 15 and 24) are then at 11, 12 and 21
+    let fields: Vec<&str> = stat
+        .rsplit_once(')')
+        .map(|(_, rest)| rest.split_whitespace().collect())
+        .unwrap_or_default();
+    let field = |index: usize| {
+        fields
+            .get(index)
+            .and_then(|value| value.parse::<u64>().ok())
+            .ok_or_else(|| format!("Unexpected /proc/{}/stat format", pid))
+    };
+    let ticks = field(11)? + field(12)?;
+    let rss_pages = field(21)?;
+
+    let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as u64;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64;
+    Ok(ProcessUsage {
+        cpu_time: std::time::Duration::from_secs_f64(ticks as f64 / ticks_per_sec as f64),
+        rss_bytes: rss_pages * page_size,
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn single_process_usage(pid: u32) -> Result<ProcessUsage, String> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "time=,rss=", "-p", &pid.to_string()])
+        .output()
+        .map_err(|e| format!("Failed to run ps: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("Process {} is not running", pid));
+    }
+
+    // e.g. "   1:02.34  51234": CPU time as [hh:]mm:ss.cc, RSS in KiB
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut fields = stdout.split_whitespace();
+    let (Some(time), Some(rss_kib)) = (fields.next(), fields.next()) else {
+        return Err(format!("Unexpected ps output: {}", stdout.trim()));
+    };
+    let secs = time
+        .split(':')
+        .try_fold(0.0, |total, part| part.parse::<f64>().map(|value| total * 60.0 + value))
+        .map_err(|_| format!("Unexpected CPU time from ps: {}", time))?;
+    let rss_kib: u64 = rss_kib
+        .parse()
+        .map_err(|_| format!("Unexpected RSS from ps: {}", rss_kib))?;
+    Ok(ProcessUsage {
+        cpu_time: std::time::Duration::from_secs_f64(secs),
+        rss_bytes: rss_kib * 1024,
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn single_process_usage(pid: u32) -> Result<ProcessUsage, String> {
+    use windows_sys::Win32::Foundation::{CloseHandle, FILETIME};
+    use windows_sys::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows_sys::Win32::System::Threading::{
+        GetProcessTimes, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    // FILETIMEs count 100ns intervals
+    fn filetime_ticks(time: &FILETIME) -> u64 {
+        (u64::from(time.dwHighDateTime) << 32) | u64::from(time.dwLowDateTime)
+    }
+
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return Err(format!("Failed to open backend process: {}", std::io::Error::last_os_error()));
+        }
+        let mut creation: FILETIME = std::mem::zeroed();
+        let mut exit: FILETIME = std::mem::zeroed();
+        let mut kernel: FILETIME = std::mem::zeroed();
+        let mut user: FILETIME = std::mem::zeroed();
+        let times_ok = GetProcessTimes(process, &mut creation, &mut exit, &mut kernel, &mut user);
+        let mut counters: PROCESS_MEMORY_COUNTERS = std::mem::zeroed();
+        counters.cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        let memory_ok = GetProcessMemoryInfo(process, &mut counters, counters.cb);
+        CloseHandle(process);
+        if times_ok == 0 || memory_ok == 0 {
+            return Err(format!("Failed to read process usage: {}", std::io::Error::last_os_error()));
+        }
+
+        Ok(ProcessUsage {
+            cpu_time: std::time::Duration::from_nanos(
+                (filetime_ticks(&kernel) + filetime_ticks(&user)) * 100,
+            ),
+            rss_bytes: counters.WorkingSetSize as u64,
+        })
+    }
+}
@@ -0,0 +1,828 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+pub const DEFAULT_LOG_BUFFER_CAPACITY: usize = 1000;
+pub const DEFAULT_MAX_LOG_LINE_LENGTH: usize = 8192;
+
+// Current log file inside the app log dir; rotated segments are named backend-<suffix>.log
+pub const LOG_FILE_NAME: &str = "backend.log";
+
+// A single captured line of backend output
+#[derive(Clone, Serialize)]
+pub struct LogLine {
+    pub stream: &'static str, // "stdout" or "stderr"
+    pub line: String,
+    pub timestamp: u64, // Milliseconds since the Unix epoch
+}
+
+// In-memory ring buffer of recent backend output, optionally persisted to a log file
+pub struct LogBuffer {
+    lines: VecDeque<LogLine>,
+    capacity: usize,
+    file: Option<File>,
+    log_dir: Option<PathBuf>,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::with_capacity(capacity.min(DEFAULT_LOG_BUFFER_CAPACITY)),
+            capacity,
+            file: None,
+            log_dir: None,
+        }
+    }
+
+    // Start persisting output to <log_dir>/backend.log (appending to any existing file)
+    pub fn attach_file(&mut self, log_dir: PathBuf) -> Result<(), String> {
+        std::fs::create_dir_all(&log_dir)
+            .map_err(|e| format!("Failed to create log dir: {}", e))?;
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_dir.join(LOG_FILE_NAME))
+            .map_err(|e| format!("Failed to open log file: {}", e))?;
+
+        self.file = Some(file);
+        self.log_dir = Some(log_dir);
+        Ok(())
+    }
+
+    pub fn push(&mut self, line: LogLine) {
+        if let Some(file) = self.file.as_mut() {
+            let _ = writeln!(file, "{} [{}] {}", line.timestamp, line.stream, line.line);
+        }
+
+        if self.capacity == 0 {
+            return;
+        }
+        while self.lines.len() >= self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    // Change the capacity, dropping the oldest lines if it shrank
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.lines.len() > capacity {
+            self.lines.pop_front();
+        }
+    }
+
+    pub fn lines(&self) -> Vec<LogLine> {
+        self.lines.iter().cloned().collect()
+    }
+
+    // The last n buffered lines from the given stream, oldest first
+    pub fn recent(&self, stream: &str, n: usize) -> Vec<LogLine> {
+        let mut lines: Vec<LogLine> = self
+            .lines
+            .iter()
+            .rev()
+            .filter(|line| line.stream == stream)
+            .take(n)
+            .cloned()
+            .collect();
+        lines.reverse();
+        lines
+    }
+
+    // Most recent buffered line logged at min_level or above (a LOG_LEVELS rank), from any stream
+    // Backends log plenty of INFO to stderr, so the last stderr line is rarely an error
+    pub fn last_line_at_level(&self, min_level: usize) -> Option<String> {
+        self.lines
+            .iter()
+            .rev()
+            .find(|line| line_level(&line.line).and_then(level_rank).is_some_and(|rank| rank >= min_level))
+            .map(|line| line.line.clone())
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    // The persisted log files, oldest first: the rotated segments, then the current file
+    // Flushes the current file so reading them (with read_persisted_lines) sees every line;
+    // None if output isn't being persisted
+    pub fn persisted_files(&mut self) -> Option<Vec<PathBuf>> {
+        if let Some(file) = self.file.as_mut() {
+            let _ = file.flush();
+        }
+        let log_dir = self.log_dir.as_ref()?;
+
+        let mut segments: Vec<(u64, PathBuf)> = rotated_log_files(log_dir)
+            .into_iter()
+            .filter_map(|path| Some((rotated_at(&path)?, path)))
+            .collect();
+        segments.sort();
+        let mut files: Vec<PathBuf> = segments.into_iter().map(|(_, path)| path).collect();
+        files.push(log_dir.join(LOG_FILE_NAME));
+        Some(files)
+    }
+
+    // Close the current log file, rename it to backend-<ms>.log and start a fresh one
+    // Returns the path of the rotated segment
+    pub fn rotate(&mut self) -> Result<PathBuf, String> {
+        let log_dir = self
+            .log_dir
+            .clone()
+            .ok_or_else(|| "Backend logs are not being persisted".to_string())?;
+
+        if let Some(mut file) = self.file.take() {
+            let _ = file.flush();
+        }
+
+        let current = log_dir.join(LOG_FILE_NAME);
+        let rotated = log_dir.join(format!("backend-{}.log", now_millis()));
+        let renamed = std::fs::rename(&current, &rotated)
+            .map_err(|e| format!("Failed to rotate log file: {}", e));
+
+        // Reopen even if the rename failed so logging carries on
+        self.attach_file(log_dir)?;
+        renamed.map(|_| rotated)
+    }
+
+    // Truncate the current log file and delete rotated segments, returning the bytes freed
+    // The current file is truncated through its open handle, so logging keeps working
+    pub fn clear_files(&mut self) -> u64 {
+        let mut freed = 0;
+
+        if let Some(file) = self.file.as_ref() {
+            let len = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+            if file.set_len(0).is_ok() {
+                freed += len;
+            }
+        }
+
+        if let Some(log_dir) = self.log_dir.as_ref() {
+            for path in rotated_log_files(log_dir) {
+                let len = path.metadata().map(|meta| meta.len()).unwrap_or(0);
+                if std::fs::remove_file(&path).is_ok() {
+                    freed += len;
+                }
+            }
+        }
+
+        freed
+    }
+}
+
+// Rotated log segments in the log dir (everything but the current file)
+pub fn rotated_log_files(log_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(log_dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name != LOG_FILE_NAME && name.starts_with("backend") && name.ends_with(".log")
+        })
+        .map(|entry| entry.path())
+        .collect()
+}
+
+// When a segment was rotated (epoch ms), from its backend-<ms>.log name; its lines are all older
+fn rotated_at(path: &Path) -> Option<u64> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("backend-")?
+        .strip_suffix(".log")?
+        .parse()
+        .ok()
+}
+
+// Lines from persisted log files (oldest first, as returned by LogBuffer::persisted_files)
+// within [since, until] (epoch ms): segments rotated before `since` are skipped, and reading
+// stops at the first line past `until`. Blocking, so call it off the async runtime
+pub fn read_persisted_lines(files: &[PathBuf], since: Option<u64>, until: Option<u64>) -> Vec<LogLine> {
+    use std::io::BufRead;
+
+    let mut lines = Vec::new();
+    for path in files {
+        if since.is_some_and(|since| rotated_at(path).is_some_and(|rotated_at| rotated_at < since)) {
+            continue;
+        }
+        let Ok(file) = File::open(path) else {
+            continue;
+        };
+        for line in std::io::BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| parse_log_file_line(&line))
+        {
+            if until.is_some_and(|until| line.timestamp > until) {
+                return lines;
+            }
+            if since.is_none_or(|since| line.timestamp >= since) {
+                lines.push(line);
+            }
+        }
+    }
+    lines
+}
+
+// Write a timestamped crash report into the crashes dir, returning its path
+pub fn write_crash_report(crash_dir: &Path, contents: &str) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(crash_dir)
+        .map_err(|e| format!("Failed to create crash dir: {}", e))?;
+    let path = crash_dir.join(format!("crash-{}.txt", now_millis()));
+    std::fs::write(&path, contents)
+        .map_err(|e| format!("Failed to write crash report: {}", e))?;
+    Ok(path)
+}
+
+pub type SharedLogBuffer = Arc<Mutex<LogBuffer>>;
+
+// Parse a "<timestamp> [<stream>] <line>" row written by LogBuffer::push
+fn parse_log_file_line(row: &str) -> Option<LogLine> {
+    let (timestamp, rest) = row.split_once(' ')?;
+    let (stream, line) = rest.split_once("] ")?;
+    let stream = match stream.strip_prefix('[')? {
+        "stdout" => "stdout",
+        "stderr" => "stderr",
+        _ => return None,
+    };
+    Some(LogLine {
+        stream,
+        line: line.to_string(),
+        timestamp: timestamp.parse().ok()?,
+    })
+}
+
+// Log levels in increasing severity, as named by Python's logging module
+pub const LOG_LEVELS: [&str; 5] = ["debug", "info", "warning", "error", "critical"];
+pub const ERROR_LEVEL: usize = 3;
+
+// Severity of a level name (0 = debug), accepting common aliases
+pub fn level_rank(level: &str) -> Option<usize> {
+    let level = match level.to_ascii_lowercase().as_str() {
+        "warn" => "warning".to_string(),
+        "fatal" => "critical".to_string(),
+        "trace" => "debug".to_string(),
+        level => level.to_string(),
+    };
+    LOG_LEVELS.iter().position(|name| *name == level)
+}
+
+// The level a backend line was logged at: the "level"/"levelname" field of a JSON log
+// record, or a level word leading a plain line ("ERROR:    ...", "2024-01-01 12:00:00 WARNING ...")
+pub fn line_level(line: &str) -> Option<&'static str> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('{') {
+        if let Ok(serde_json::Value::Object(record)) = serde_json::from_str(trimmed) {
+            let level = ["level", "levelname", "severity"]
+                .iter()
+                .find_map(|key| record.get(*key)?.as_str())?;
+            return level_rank(level).map(|rank| LOG_LEVELS[rank]);
+        }
+    }
+
+    // Only look at the first few words (past any timestamp) so a message mentioning
+    // "error" isn't misread
+    trimmed
+        .split(|c: char| c.is_whitespace() || matches!(c, ':' | '[' | ']' | '|' | '-'))
+        .filter(|word| !word.is_empty() && !word.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | ',')))
+        .take(4)
+        .find_map(|word| {
+            word.chars()
+                .all(|c| c.is_ascii_uppercase())
+                .then(|| level_rank(word))
+                .flatten()
+        })
+        .map(|rank| LOG_LEVELS[rank])
+}
+
+// A captured line with the level it was logged at
+#[derive(Clone, Serialize)]
+pub struct LeveledLogLine {
+    #[serde(flatten)]
+    pub line: LogLine,
+    pub level: Option<&'static str>,
+}
+
+// Keep the lines at or above min_level within [since, until] (epoch ms, inclusive)
+// Lines without a level of their own (traceback frames, wrapped messages) take the level
+// of the previous line on the same stream, so an error keeps its traceback
+pub fn filter_lines(
+    lines: Vec<LogLine>,
+    min_level: Option<usize>,
+    since: Option<u64>,
+    until: Option<u64>,
+) -> Vec<LeveledLogLine> {
+    let mut last_level: std::collections::HashMap<&'static str, &'static str> = Default::default();
+    lines
+        .into_iter()
+        .filter_map(|line| {
+            let level = match line_level(&line.line) {
+                Some(level) => {
+                    last_level.insert(line.stream, level);
+                    Some(level)
+                }
+                None => last_level.get(line.stream).copied(),
+            };
+            let in_window = since.is_none_or(|since| line.timestamp >= since)
+                && until.is_none_or(|until| line.timestamp <= until);
+            let severe_enough = match min_level {
+                Some(min) => level.and_then(level_rank).is_some_and(|rank| rank >= min),
+                None => true,
+            };
+            (in_window && severe_enough).then_some(LeveledLogLine { line, level })
+        })
+        .collect()
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// Decode a raw output line, capping it at max_len bytes (0 = unlimited)
+// Only the kept prefix is decoded so a gigantic line never gets fully copied; the cut is moved
+// back to a character boundary so it doesn't produce a replacement character
+pub fn decode_line(bytes: &[u8], max_len: usize) -> String {
+    if max_len == 0 || bytes.len() <= max_len {
+        return String::from_utf8_lossy(bytes).to_string();
+    }
+
+    let mut end = max_len;
+    // UTF-8 continuation bytes look like 0b10xxxxxx
+    while end > max_len.saturating_sub(3) && bytes[end] & 0xC0 == 0x80 {
+        end -= 1;
+    }
+    format!(
+        "{}… [truncated {} bytes]",
+        String::from_utf8_lossy(&bytes[..end]),
+        bytes.len() - end
+    )
+}
+
+// How captured output needed fixing up, to spot backends with Windows line endings or
+// non-UTF-8 (e.g. code page) output
+#[derive(Default)]
+pub struct DecodeStats {
+    lines: AtomicU64,
+    crlf_lines: AtomicU64,      // Ended in \r\n
+    cr_lines: AtomicU64,        // Ended in a bare \r (progress bars redrawing their line)
+    invalid_utf8_lines: AtomicU64,  // Had bytes that decoded to replacement characters
+}
+
+#[derive(Serialize)]
+pub struct DecodeStatsSnapshot {
+    lines: u64,
+    crlf_lines: u64,
+    cr_lines: u64,
+    invalid_utf8_lines: u64,
+}
+
+impl DecodeStats {
+    pub fn snapshot(&self) -> DecodeStatsSnapshot {
+        DecodeStatsSnapshot {
+            lines: self.lines.load(Ordering::Relaxed),
+            crlf_lines: self.crlf_lines.load(Ordering::Relaxed),
+            cr_lines: self.cr_lines.load(Ordering::Relaxed),
+            invalid_utf8_lines: self.invalid_utf8_lines.load(Ordering::Relaxed),
+        }
+    }
+}
+
+pub type SharedDecodeStats = Arc<DecodeStats>;
+
+// Turns the lines of one output stream (as split by the shell plugin at \n or \r) into
+// display lines without their line ending
+// A final chunk without a line ending that stops mid-character is held back in case more
+// follows; flush returns it once the stream has ended
+#[derive(Default)]
+pub struct LineDecoder {
+    pending: Vec<u8>,
+    secret: Option<String>,
+}
+
+impl LineDecoder {
+    // A decoder that replaces the secret with <redacted>, before a long line is truncated
+    // so a secret cut at the truncation point can't slip through
+    pub fn redacting(secret: Option<String>) -> Self {
+        Self {
+            pending: Vec::new(),
+            secret: secret.filter(|secret| !secret.is_empty()),
+        }
+    }
+
+    // Returns None when the whole chunk is held back as an incomplete sequence
+    pub fn decode(&mut self, bytes: &[u8], max_len: usize, stats: &DecodeStats) -> Option<String> {
+        let mut data = std::mem::take(&mut self.pending);
+        data.extend_from_slice(bytes);
+
+        let mut content = data.as_slice();
+        if let Some(stripped) = content.strip_suffix(b"\r\n") {
+            content = stripped;
+            stats.crlf_lines.fetch_add(1, Ordering::Relaxed);
+        } else if let Some(stripped) = content.strip_suffix(b"\n") {
+            content = stripped;
+        } else if let Some(stripped) = content.strip_suffix(b"\r") {
+            content = stripped;
+            stats.cr_lines.fetch_add(1, Ordering::Relaxed);
+        } else if let Err(e) = std::str::from_utf8(content) {
+            // No line ending: the stream paused mid-line, possibly mid-character
+            if e.error_len().is_none() {
+                self.pending = data.split_off(e.valid_up_to());
+                if data.is_empty() {
+                    return None;
+                }
+                content = data.as_slice();
+            }
+        }
+
+        Some(self.finish(content, max_len, stats))
+    }
+
+    // Decode whatever is still held back; call once the stream has ended
+    pub fn flush(&mut self, max_len: usize, stats: &DecodeStats) -> Option<String> {
+        let pending = std::mem::take(&mut self.pending);
+        (!pending.is_empty()).then(|| self.finish(&pending, max_len, stats))
+    }
+
+    fn finish(&self, content: &[u8], max_len: usize, stats: &DecodeStats) -> String {
+        if std::str::from_utf8(content).is_err() {
+            stats.invalid_utf8_lines.fetch_add(1, Ordering::Relaxed);
+        }
+        stats.lines.fetch_add(1, Ordering::Relaxed);
+
+        match &self.secret {
+            Some(secret) => decode_line(&crate::secrets::redact_bytes(content, secret), max_len),
+            None => decode_line(content, max_len),
+        }
+    }
+}
+
+// Counters for log events sent over the Tauri event bridge, to spot IPC pressure
+// from verbose backends
+pub struct EventStats {
+    started: Instant,
+    emitted: AtomicU64,
+    dropped: AtomicU64,    // emit() failed
+    coalesced: AtomicU64,  // Lines folded into a batch event instead of sent on their own
+    peak_per_second: AtomicU64,
+    window: std::sync::Mutex<RateWindow>,
+}
+
+// Event counts for the current and the previous whole second since `started`
+#[derive(Default)]
+struct RateWindow {
+    second: u64,
+    current: u64,
+    previous: u64,
+}
+
+#[derive(Serialize)]
+pub struct EventStatsSnapshot {
+    emitted: u64,
+    dropped: u64,
+    coalesced: u64,
+    events_per_second: u64,  // Over the last complete second
+    peak_per_second: u64,
+    average_per_second: f64,
+}
+
+impl EventStats {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            emitted: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+            peak_per_second: AtomicU64::new(0),
+            window: std::sync::Mutex::new(RateWindow::default()),
+        }
+    }
+
+    // Record one emit() call and whether it succeeded
+    pub fn record_emit(&self, ok: bool) {
+        if !ok {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.emitted.fetch_add(1, Ordering::Relaxed);
+
+        let second = self.started.elapsed().as_secs();
+        let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+        if second != window.second {
+            self.peak_per_second.fetch_max(window.current, Ordering::Relaxed);
+            window.previous = if second == window.second + 1 { window.current } else { 0 };
+            window.current = 0;
+            window.second = second;
+        }
+        window.current += 1;
+    }
+
+    pub fn record_coalesced(&self, lines: u64) {
+        self.coalesced.fetch_add(lines, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> EventStatsSnapshot {
+        let elapsed = self.started.elapsed();
+        let second = elapsed.as_secs();
+        let (events_per_second, current) = {
+            let window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+            let last = if second == window.second {
+                window.previous
+            } else if second == window.second + 1 {
+                window.current
+            } else {
+                0
+            };
+            (last, window.current)
+        };
+
+        let emitted = self.emitted.load(Ordering::Relaxed);
+        EventStatsSnapshot {
+            emitted,
+            dropped: self.dropped.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            events_per_second,
+            peak_per_second: self.peak_per_second.load(Ordering::Relaxed).max(current),
+            average_per_second: emitted as f64 / elapsed.as_secs_f64().max(1.0),
+        }
+    }
+}
+
+impl Default for EventStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type SharedEventStats = Arc<EventStats>;
+
+// Lines waiting to be sent together as one backend-log-batch event, in arrival order
+#[derive(Default)]
+pub struct LogBatch {
+    lines: std::sync::Mutex<Vec<LogLine>>,
+    closed: AtomicBool,
+}
+
+impl LogBatch {
+    pub fn push(&self, line: LogLine) {
+        self.lines.lock().unwrap_or_else(|e| e.into_inner()).push(line);
+    }
+
+    // Hand the pending lines (if any) to `send` while still holding the lock, so the timer's
+    // flush and the final one on exit can't deliver their batches out of order
+    pub fn flush(&self, send: impl FnOnce(Vec<LogLine>)) {
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        if !lines.is_empty() {
+            send(std::mem::take(&mut *lines));
+        }
+    }
+
+    // No more lines will be pushed; the flusher sends what's left and stops
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+}
+
+// Remove ANSI escape sequences (colors, cursor movement, OSC titles/links) from a line
+// The backend's logging libraries may assume a TTY even when we capture their output
+pub fn strip_ansi(line: &str) -> String {
+    if !line.contains('\x1b') {
+        return line.to_string();
+    }
+
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: ESC [ params... final byte in @..~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: ESC ] ... terminated by BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Two-character sequences such as ESC ( B
+            Some('(') | Some(')') => {
+                chars.next();
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_holds_back_a_character_split_across_chunks() {
+        let stats = DecodeStats::default();
+        let mut decoder = LineDecoder::default();
+
+        // "é" is 0xC3 0xA9; the first chunk stops after its first byte
+        assert_eq!(decoder.decode(b"caf\xC3", 0, &stats).as_deref(), Some("caf"));
+        assert_eq!(decoder.decode(b"\xA9\n", 0, &stats).as_deref(), Some("é"));
+        assert_eq!(stats.invalid_utf8_lines.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn decode_holds_back_a_chunk_that_is_only_a_partial_character() {
+        let stats = DecodeStats::default();
+        let mut decoder = LineDecoder::default();
+
+        // "€" is 0xE2 0x82 0xAC
+        assert_eq!(decoder.decode(b"\xE2\x82", 0, &stats), None);
+        assert_eq!(decoder.decode(b"\xAC\n", 0, &stats).as_deref(), Some("€"));
+        assert_eq!(stats.lines.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn decode_strips_line_endings() {
+        let stats = DecodeStats::default();
+        let mut decoder = LineDecoder::default();
+
+        assert_eq!(decoder.decode(b"windows\r\n", 0, &stats).as_deref(), Some("windows"));
+        assert_eq!(decoder.decode(b"unix\n", 0, &stats).as_deref(), Some("unix"));
+        assert_eq!(decoder.decode(b"50%\r", 0, &stats).as_deref(), Some("50%"));
+        assert_eq!(stats.crlf_lines.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.cr_lines.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.lines.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn decode_does_not_hold_back_invalid_bytes() {
+        let stats = DecodeStats::default();
+        let mut decoder = LineDecoder::default();
+
+        // 0xFF can never start a character, so there is nothing to wait for
+        assert_eq!(decoder.decode(b"bad\xFF", 0, &stats).as_deref(), Some("bad\u{FFFD}"));
+        assert_eq!(decoder.flush(0, &stats), None);
+        assert_eq!(stats.invalid_utf8_lines.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn flush_returns_a_truncated_tail() {
+        let stats = DecodeStats::default();
+        let mut decoder = LineDecoder::default();
+
+        // The stream ends before the rest of "€" arrives
+        assert_eq!(decoder.decode(b"ab\xE2\x82", 0, &stats).as_deref(), Some("ab"));
+        assert_eq!(decoder.flush(0, &stats).as_deref(), Some("\u{FFFD}"));
+        assert_eq!(decoder.flush(0, &stats), None);
+        assert_eq!(stats.invalid_utf8_lines.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.lines.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn decode_line_truncates_at_a_character_boundary() {
+        // The cut at 4 bytes falls inside "é", so it moves back before it
+        assert_eq!(decode_line("abcé".as_bytes(), 4), "abc… [truncated 2 bytes]");
+        assert_eq!(decode_line(b"short", 0), "short");
+        assert_eq!(decode_line(b"short", 5), "short");
+    }
+
+    #[test]
+    fn decode_redacts_a_secret_straddling_the_truncation_point() {
+        let stats = DecodeStats::default();
+        let mut decoder = LineDecoder::redacting(Some("supersecret".to_string()));
+
+        // Truncating first would keep "supe", which redaction would then no longer match
+        let line = decoder.decode(b"token=supersecret\n", 10, &stats).unwrap();
+        assert!(!line.contains("supe"), "{}", line);
+        assert_eq!(line, "token=<red… [truncated 6 bytes]");
+    }
+
+    #[test]
+    fn redacting_ignores_an_empty_secret() {
+        let stats = DecodeStats::default();
+        let mut decoder = LineDecoder::redacting(Some(String::new()));
+
+        assert_eq!(decoder.decode(b"nothing to hide\n", 0, &stats).as_deref(), Some("nothing to hide"));
+    }
+
+    fn line(stream: &'static str, text: &str, timestamp: u64) -> LogLine {
+        LogLine {
+            stream,
+            line: text.to_string(),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn line_level_reads_json_records() {
+        assert_eq!(line_level(r#"{"levelname": "ERROR", "message": "boom"}"#), Some("error"));
+        assert_eq!(line_level(r#"  {"level": "warn", "msg": "slow"}"#), Some("warning"));
+        assert_eq!(line_level(r#"{"severity": "CRITICAL"}"#), Some("critical"));
+        assert_eq!(line_level(r#"{"message": "no level"}"#), None);
+    }
+
+    #[test]
+    fn line_level_reads_a_leading_level_word() {
+        assert_eq!(line_level("ERROR:    Exception in ASGI application"), Some("error"));
+        assert_eq!(line_level("INFO:     127.0.0.1:5000 - \"GET / HTTP/1.1\" 200"), Some("info"));
+        // Timestamps are skipped
+        assert_eq!(line_level("2024-01-01 12:00:00,123 WARNING disk almost full"), Some("warning"));
+        assert_eq!(line_level("[2024-01-01 12:00:00] [ERROR] request failed"), Some("error"));
+        assert_eq!(line_level("app | FATAL | giving up"), Some("critical"));
+    }
+
+    #[test]
+    fn line_level_ignores_a_message_mentioning_a_level() {
+        // Only uppercase words count
+        assert_eq!(line_level("Retrying after a connection error"), None);
+        assert_eq!(line_level("info: this is not how uvicorn logs"), None);
+        // Only the first few words are looked at
+        assert_eq!(line_level("Processed all queued items, then ERROR count unchanged"), None);
+        assert_eq!(line_level("Traceback (most recent call last):"), None);
+        assert_eq!(line_level(""), None);
+    }
+
+    #[test]
+    fn filter_lines_carries_the_level_onto_traceback_lines() {
+        let lines = vec![
+            line("stderr", "INFO:     Started server process", 1),
+            line("stderr", "ERROR:    Exception in ASGI application", 2),
+            line("stderr", "Traceback (most recent call last):", 3),
+            line("stderr", "  File \"main.py\", line 1, in <module>", 4),
+            line("stdout", "plain output", 5),
+            line("stderr", "INFO:     Shutting down", 6),
+        ];
+
+        let filtered = filter_lines(lines, Some(ERROR_LEVEL), None, None);
+        let kept: Vec<(&str, Option<&str>)> = filtered
+            .iter()
+            .map(|leveled| (leveled.line.line.as_str(), leveled.level))
+            .collect();
+        assert_eq!(
+            kept,
+            vec![
+                ("ERROR:    Exception in ASGI application", Some("error")),
+                ("Traceback (most recent call last):", Some("error")),
+                ("  File \"main.py\", line 1, in <module>", Some("error")),
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_lines_keeps_streams_apart() {
+        let lines = vec![
+            line("stderr", "ERROR: boom", 1),
+            line("stdout", "not an error", 2),
+        ];
+
+        let filtered = filter_lines(lines, None, None, None);
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered[0].level, Some("error"));
+        assert_eq!(filtered[1].level, None);
+    }
+
+    #[test]
+    fn filter_lines_applies_the_time_window() {
+        let lines = vec![
+            line("stderr", "WARNING: early", 10),
+            line("stderr", "WARNING: inside", 20),
+            line("stderr", "WARNING: late", 30),
+        ];
+
+        let filtered = filter_lines(lines.clone(), None, Some(20), Some(20));
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].line.line, "WARNING: inside");
+
+        // Lines without any level are dropped once a minimum is given
+        let filtered = filter_lines(vec![line("stdout", "plain", 1)], Some(0), None, None);
+        assert!(filtered.is_empty());
+        assert_eq!(filter_lines(lines, Some(ERROR_LEVEL), None, None).len(), 0);
+    }
+}
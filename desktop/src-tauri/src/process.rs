@@ -0,0 +1,298 @@
+use serde::Serialize;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+#[derive(Clone, Serialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub name: String,
+}
+
+// Snapshot of all processes on the system via `ps`
+#[cfg(not(target_os = "windows"))]
+pub fn list_processes() -> Result<Vec<ProcessInfo>, String> {
+    let output = std::process::Command::new("ps")
+        .args(["-A", "-o", "pid=,ppid=,comm="])
+        .output()
+        .map_err(|e| format!("Failed to run ps: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("ps failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            // comm may itself contain spaces
+            let name = fields.collect::<Vec<_>>().join(" ");
+            Some(ProcessInfo { pid, ppid, name })
+        })
+        .collect())
+}
+
+// Snapshot of all processes on the system via CIM (wmic is deprecated)
+#[cfg(target_os = "windows")]
+pub fn list_processes() -> Result<Vec<ProcessInfo>, String> {
+    let output = std::process::Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            "Get-CimInstance Win32_Process | Select-Object ProcessId,ParentProcessId,Name | ConvertTo-Csv -NoTypeInformation",
+        ])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to query processes: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Process query failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .skip(1) // Header row
+        .filter_map(|line| {
+            let fields = parse_csv_line(line);
+            let pid = fields.first()?.parse().ok()?;
+            let ppid = fields.get(1)?.parse().ok()?;
+            let name = fields.get(2)?.clone();
+            Some(ProcessInfo { pid, ppid, name })
+        })
+        .collect())
+}
+
+// Split a CSV line of double-quoted fields ("a","b,c","d ""e""")
+#[cfg(target_os = "windows")]
+pub fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.trim().chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+// Look up a pid with tasklist, returning its image name if it is running
+// Uses CSV output and compares the PID column exactly, rather than substring-matching the
+// table (which both missed renamed sidecars and matched the pid inside other columns)
+#[cfg(target_os = "windows")]
+pub fn tasklist_image_name(pid: u32) -> Result<Option<String>, String> {
+    let output = std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run tasklist: {}", e))?;
+
+    // Rows look like: "python-backend.exe","1234","Console","1","50,000 K"
+    // When nothing matches, tasklist prints an "INFO: No tasks ..." line instead
+    Ok(String::from_utf8_lossy(&output.stdout).lines().find_map(|line| {
+        let fields = parse_csv_line(line);
+        match (fields.first(), fields.get(1)) {
+            (Some(image_name), Some(row_pid)) if row_pid.parse::<u32>() == Ok(pid) => {
+                Some(image_name.clone())
+            }
+            _ => None,
+        }
+    }))
+}
+
+// Whether a process with the given pid currently exists
+#[cfg(unix)]
+pub fn is_process_alive(pid: u32) -> bool {
+    // Signal 0 performs the permission/existence check without sending anything;
+    // EPERM means the process exists but belongs to someone else
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(target_os = "windows")]
+pub fn is_process_alive(pid: u32) -> bool {
+    // If tasklist itself fails, err on the side of "alive" so we don't clear a live backend
+    tasklist_image_name(pid).map(|name| name.is_some()).unwrap_or(true)
+}
+
+// When the process started, as an opaque value that only needs to compare equal for the same
+// process: together with the pid it identifies a process even after the pid is reused
+#[cfg(target_os = "linux")]
+pub fn start_time(pid: u32) -> Option<String> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name is parenthesized and may contain spaces, so count fields after it;
+    // starttime is field 22, the 20th after the name
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(19).map(str::to_string)
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn start_time(pid: u32) -> Option<String> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "lstart=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let start_time = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !start_time.is_empty()).then_some(start_time)
+}
+
+#[cfg(target_os = "windows")]
+pub fn start_time(pid: u32) -> Option<String> {
+    let output = std::process::Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            &format!("(Get-Process -Id {}).StartTime.ToFileTimeUtc()", pid),
+        ])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    let start_time = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !start_time.is_empty()).then_some(start_time)
+}
+
+// Forcefully kill a process we don't hold a child handle for (e.g. a recovered backend)
+#[cfg(unix)]
+pub fn kill(pid: u32) -> bool {
+    unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) == 0 }
+}
+
+#[cfg(target_os = "windows")]
+pub fn kill(pid: u32) -> bool {
+    std::process::Command::new("taskkill")
+        .args(["/F", "/T", "/PID", &pid.to_string()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+// Addresses with a TCP socket listening on the given port, from /proc/net/tcp{,6}
+#[cfg(target_os = "linux")]
+pub fn listening_addresses(port: u16) -> Result<Vec<std::net::IpAddr>, String> {
+    // The kernel prints addresses as native-endian 32-bit words in hex
+    fn decode(hex: &str) -> Option<std::net::IpAddr> {
+        let words = (0..hex.len() / 8)
+            .map(|i| u32::from_str_radix(&hex[i * 8..i * 8 + 8], 16).map(u32::to_ne_bytes))
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+        match words.as_slice() {
+            [a] => Some(std::net::Ipv4Addr::from(*a).into()),
+            [a, b, c, d] => {
+                let mut bytes = [0u8; 16];
+                for (chunk, word) in bytes.chunks_mut(4).zip([a, b, c, d]) {
+                    chunk.copy_from_slice(word);
+                }
+                Some(std::net::Ipv6Addr::from(bytes).into())
+            }
+            _ => None,
+        }
+    }
+
+    let mut addresses = Vec::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        let Ok(contents) = std::fs::read_to_string(table) else {
+            continue;
+        };
+        for line in contents.lines().skip(1) {
+            let mut fields = line.split_whitespace();
+            let (Some(local), Some(state)) = (fields.nth(1), fields.nth(1)) else {
+                continue;
+            };
+            // 0A = TCP_LISTEN
+            if state != "0A" {
+                continue;
+            }
+            let Some((address, local_port)) = local.split_once(':') else {
+                continue;
+            };
+            if u16::from_str_radix(local_port, 16) == Ok(port) {
+                addresses.extend(decode(address));
+            }
+        }
+    }
+    Ok(addresses)
+}
+
+// Addresses with a TCP socket listening on the given port, via lsof
+#[cfg(target_os = "macos")]
+pub fn listening_addresses(port: u16) -> Result<Vec<std::net::IpAddr>, String> {
+    let output = std::process::Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-Fn"])
+        .output()
+        .map_err(|e| format!("Failed to run lsof: {}", e))?;
+
+    // -Fn prints one "n<address>:<port>" line per socket, e.g. "n127.0.0.1:8000", "n*:8000", "n[::1]:8000"
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_prefix('n'))
+        .filter_map(|name| name.rsplit_once(':').map(|(address, _)| address))
+        .filter_map(|address| match address {
+            "*" => Some(std::net::Ipv4Addr::UNSPECIFIED.into()),
+            address => address.trim_matches(['[', ']']).parse().ok(),
+        })
+        .collect())
+}
+
+// Addresses with a TCP socket listening on the given port, via netstat
+#[cfg(target_os = "windows")]
+pub fn listening_addresses(port: u16) -> Result<Vec<std::net::IpAddr>, String> {
+    let output = std::process::Command::new("netstat")
+        .args(["-ano", "-p", "TCP"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run netstat: {}", e))?;
+    let output_v6 = std::process::Command::new("netstat")
+        .args(["-ano", "-p", "TCPv6"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run netstat: {}", e))?;
+
+    // Rows look like: "TCP    127.0.0.1:8000    0.0.0.0:0    LISTENING    1234"
+    let stdout = [output.stdout, output_v6.stdout].concat();
+    Ok(String::from_utf8_lossy(&stdout)
+        .lines()
+        .filter(|line| line.contains("LISTENING"))
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .filter_map(|local| local.rsplit_once(':'))
+        .filter(|(_, local_port)| local_port.parse::<u16>() == Ok(port))
+        .filter_map(|(address, _)| address.trim_matches(['[', ']']).split('%').next()?.parse().ok())
+        .collect())
+}
+
+// All descendants of the given pid (children, grandchildren, ...), in breadth-first order
+pub fn descendants(root: u32) -> Result<Vec<ProcessInfo>, String> {
+    let processes = list_processes()?;
+
+    let mut result: Vec<ProcessInfo> = Vec::new();
+    let mut queue = std::collections::VecDeque::from([root]);
+    while let Some(parent) = queue.pop_front() {
+        for process in processes.iter().filter(|p| p.ppid == parent && p.pid != parent) {
+            // Guard against pid reuse creating cycles
+            if process.pid != root && !result.iter().any(|p| p.pid == process.pid) {
+                queue.push_back(process.pid);
+                result.push(process.clone());
+            }
+        }
+    }
+
+    Ok(result)
+}
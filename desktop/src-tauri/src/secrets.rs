@@ -0,0 +1,83 @@
+// Keychain entry (macOS Keychain, Windows Credential Manager, Secret Service on Linux)
+// holding the token forwarded to the backend
+const KEYCHAIN_SERVICE: &str = "com.owork.desktop";
+const BACKEND_TOKEN_ACCOUNT: &str = "backend-token";
+
+// Environment variable the backend reads its token from; never passed as an argument,
+// since args are visible to every user in process listings
+pub const BACKEND_TOKEN_ENV: &str = "OWORK_BACKEND_TOKEN";
+
+fn backend_token_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, BACKEND_TOKEN_ACCOUNT)
+        .map_err(|e| format!("Failed to open keychain entry: {}", e))
+}
+
+// Stored backend token, or None if none has been set
+pub fn load_backend_token() -> Result<Option<String>, String> {
+    match backend_token_entry()?.get_password() {
+        Ok(token) => Ok(Some(token)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read backend token from keychain: {}", e)),
+    }
+}
+
+// Store the backend token, or remove it when None
+pub fn save_backend_token(token: Option<&str>) -> Result<(), String> {
+    let entry = backend_token_entry()?;
+    match token {
+        Some(token) => entry
+            .set_password(token)
+            .map_err(|e| format!("Failed to store backend token in keychain: {}", e)),
+        None => match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to remove backend token from keychain: {}", e)),
+        },
+    }
+}
+
+// The backend token, read from the keychain once and reused by later spawns and restarts
+#[derive(Default)]
+pub struct TokenCache {
+    token: std::sync::Mutex<Option<Option<String>>>, // None until the keychain has been read
+}
+
+pub type SharedTokenCache = std::sync::Arc<TokenCache>;
+
+impl TokenCache {
+    // The token, plus the keychain error when this call was the one that failed to read it
+    // (a failed read is cached as "no token", so the error is only reported once)
+    pub fn get(&self) -> (Option<String>, Option<String>) {
+        let mut cached = self.token.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(token) = cached.as_ref() {
+            return (token.clone(), None);
+        }
+        let (token, error) = match load_backend_token() {
+            Ok(token) => (token, None),
+            Err(e) => (None, Some(e)),
+        };
+        *cached = Some(token.clone());
+        (token, error)
+    }
+
+    pub fn set(&self, token: Option<String>) {
+        *self.token.lock().unwrap_or_else(|e| e.into_inner()) = Some(token);
+    }
+}
+
+// Replace any occurrence of the token in raw captured output
+pub fn redact_bytes<'a>(bytes: &'a [u8], token: &str) -> std::borrow::Cow<'a, [u8]> {
+    let token = token.as_bytes();
+    if token.is_empty() || !bytes.windows(token.len()).any(|window| window == token) {
+        return std::borrow::Cow::Borrowed(bytes);
+    }
+
+    let mut redacted = Vec::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while let Some(index) = rest.windows(token.len()).position(|window| window == token) {
+        redacted.extend_from_slice(&rest[..index]);
+        redacted.extend_from_slice(b"<redacted>");
+        rest = &rest[index + token.len()..];
+    }
+    redacted.extend_from_slice(rest);
+    std::borrow::Cow::Owned(redacted)
+}
@@ -0,0 +1,512 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use crate::logs::{DEFAULT_LOG_BUFFER_CAPACITY, DEFAULT_MAX_LOG_LINE_LENGTH};
+
+// File (inside the app config dir) that holds the persisted backend settings
+const SETTINGS_FILE: &str = "backend-settings.json";
+
+pub const DEFAULT_HEALTH_PATH: &str = "/health";
+pub const DEFAULT_HEALTH_STATUS: &str = "healthy";
+pub const DEFAULT_HEALTH_SCHEME: &str = "http";
+pub const DEFAULT_HEALTH_HOST: &str = "127.0.0.1";
+pub const SUPPORTED_SCHEMES: [&str; 2] = ["http", "https"];
+pub const DEFAULT_MAX_RESTART_ATTEMPTS: u32 = 5;
+pub const DEFAULT_SHUTDOWN_TIMEOUT_MS: u64 = 2000;
+pub const DEFAULT_MAX_CONCURRENT_BACKEND_COMMANDS: usize = 2;
+pub const DEFAULT_READINESS_TIMEOUT_MS: u64 = 1000;
+pub const DEFAULT_STARTUP_TIMEOUT_MS: u64 = 30_000;
+pub const DEFAULT_VERSION_CHECK_TIMEOUT_MS: u64 = 10_000;
+pub const DEFAULT_FIRST_RUN_STARTUP_TIMEOUT_MS: u64 = 15 * 60 * 1000;
+
+// First launches are slowest where the OS scans new executables before running them
+#[cfg(target_os = "windows")]
+pub const DEFAULT_FIRST_LAUNCH_STARTUP_TIMEOUT_MS: u64 = 120_000;
+#[cfg(target_os = "macos")]
+pub const DEFAULT_FIRST_LAUNCH_STARTUP_TIMEOUT_MS: u64 = 90_000;
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub const DEFAULT_FIRST_LAUNCH_STARTUP_TIMEOUT_MS: u64 = 60_000;
+
+// Which Python runs the backend: the bundled PyInstaller sidecar, the backend sources
+// under a system/custom interpreter (for developers), or a separately installed backend
+// started through a configured command (system package, Docker)
+// Builds with the external-backend feature ship without the sidecar and default to External
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PythonRuntime {
+    #[cfg_attr(not(feature = "external-backend"), default)]
+    Bundled,
+    System,
+    #[cfg_attr(feature = "external-backend", default)]
+    External,
+}
+
+// Persisted backend settings
+// Unknown or missing fields fall back to their defaults so older files keep loading
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendSettings {
+    // Port to use when free, otherwise a free one is picked (OWORK_BACKEND_PORT overrides both)
+    pub preferred_port: Option<u16>,
+    pub python_runtime: PythonRuntime,
+    // Interpreter for the system runtime (None = python3/python found on the enhanced PATH)
+    pub system_python_path: Option<String>,
+    // Backend sources (containing main.py) run by the system runtime
+    pub backend_source_dir: Option<String>,
+    // Program and args the external runtime launches; "{port}" and "{host}" are substituted,
+    // e.g. ["docker", "run", "--rm", "-p", "{port}:8000", "owork-backend"]
+    pub external_command: Vec<String>,
+    pub health_path: String,
+    // Scheme and host used to reach the backend ("https" for backends serving local TLS)
+    pub health_scheme: String,
+    pub health_host: String,
+    // Required value of the "status" field in the health response (None = any 2xx response)
+    pub health_expected_status: Option<String>,
+    // Restart the backend automatically when it exits unexpectedly
+    pub auto_restart: bool,
+    pub max_restart_attempts: u32,
+    // Number of recent output lines kept in memory
+    pub log_buffer_capacity: usize,
+    // Longer output lines are truncated (0 = unlimited)
+    pub max_log_line_length: usize,
+    // Send output as backend-log-batch events every N ms instead of per line (None = per line)
+    pub log_batch_interval_ms: Option<u64>,
+    // Strip ANSI escape codes from output and run the sidecar with NO_COLOR/TERM=dumb
+    pub strip_ansi: bool,
+    // Endpoint POSTed to for a graceful shutdown before the process is killed (None = kill directly)
+    pub shutdown_path: Option<String>,
+    pub shutdown_timeout_ms: u64,
+    // Write a crash report (exit code, uptime, recent output) when the backend exits abnormally
+    pub capture_crash_reports: bool,
+    // Extra sidecar args appended after --port on every platform
+    pub extra_args: Vec<String>,
+    // Extra sidecar args for a single platform, keyed by OS ("windows", "macos", "linux")
+    pub platform_args: HashMap<String, Vec<String>>,
+    // Start the backend during app setup instead of waiting for the frontend
+    pub auto_start_backend: bool,
+    // Leave the backend running when the window closes and reattach to it on the next launch
+    pub keep_backend_alive_on_close: bool,
+    // Fall back to `$SHELL -l -c` in the version checks when the enhanced PATH isn't enough
+    pub login_shell_fallback: bool,
+    // Periodically reconcile the tracked backend state with reality (None = disabled)
+    pub watchdog_interval_secs: Option<u64>,
+    // Restart a backend that survived system sleep but no longer answers its health check
+    pub restart_on_wake: bool,
+    // Resource caps applied to the backend process when it is spawned (None = unlimited)
+    pub memory_limit_mb: Option<u64>,
+    pub cpu_limit_percent: Option<u32>,
+    // Raise the soft fd limit to the hard limit before spawning (Unix only)
+    pub raise_fd_limit: bool,
+    // Niceness applied to the backend at start (0 = normal .. 19 = lowest; None = leave as is)
+    pub backend_nice: Option<i32>,
+    // How many one-shot run_backend_command invocations may run at once; extras queue
+    pub max_concurrent_backend_commands: usize,
+    // Per-request timeout while polling the health endpoint during startup
+    pub readiness_timeout_ms: u64,
+    // How long the backend gets to become healthy after spawning
+    pub startup_timeout_ms: u64,
+    // Per-OS replacement for startup_timeout_ms, keyed like platform_args
+    pub platform_startup_timeout_ms: HashMap<String, u64>,
+    // Startup timeout until the backend has started once; antivirus scans and Gatekeeper
+    // checks make the first launch much slower (None = platform default)
+    pub first_launch_startup_timeout_ms: Option<u64>,
+    // Startup timeout for the backend's first run against its data dir, which may download
+    // models and dependencies
+    pub first_run_startup_timeout_ms: u64,
+    // Per-process timeout for the node/python version checks, including the shell fallback
+    pub version_check_timeout_ms: u64,
+    // Where the backend stores its data, passed as OWORK_DATA_DIR (None = backend default)
+    pub backend_data_dir: Option<String>,
+    // Give the sidecar only the PATH directories containing node/python/git
+    pub minimal_path: bool,
+    // Concurrent requests the backend handles, passed as --concurrency (None = backend default)
+    pub backend_concurrency: Option<u32>,
+    // Max request body size the backend accepts, passed as OWORK_MAX_BODY_SIZE (None = backend default)
+    pub max_body_size_bytes: Option<u64>,
+    // Open devtools when a backend-fatal event fires, even in release builds (opt-in)
+    pub devtools_on_fatal: bool,
+    // Debug-level logging of our own process management to the app log (OWORK_VERBOSE_LOG=1 forces it)
+    pub verbose_logging: bool,
+}
+
+impl Default for BackendSettings {
+    fn default() -> Self {
+        Self {
+            preferred_port: None,
+            python_runtime: PythonRuntime::default(),
+            system_python_path: None,
+            backend_source_dir: None,
+            external_command: Vec::new(),
+            health_path: DEFAULT_HEALTH_PATH.to_string(),
+            health_scheme: DEFAULT_HEALTH_SCHEME.to_string(),
+            health_host: DEFAULT_HEALTH_HOST.to_string(),
+            health_expected_status: Some(DEFAULT_HEALTH_STATUS.to_string()),
+            auto_restart: false,
+            max_restart_attempts: DEFAULT_MAX_RESTART_ATTEMPTS,
+            log_buffer_capacity: DEFAULT_LOG_BUFFER_CAPACITY,
+            max_log_line_length: DEFAULT_MAX_LOG_LINE_LENGTH,
+            log_batch_interval_ms: None,
+            strip_ansi: true,
+            shutdown_path: None,
+            shutdown_timeout_ms: DEFAULT_SHUTDOWN_TIMEOUT_MS,
+            capture_crash_reports: false,
+            extra_args: Vec::new(),
+            platform_args: HashMap::new(),
+            auto_start_backend: false,
+            keep_backend_alive_on_close: false,
+            login_shell_fallback: true,
+            watchdog_interval_secs: None,
+            restart_on_wake: false,
+            memory_limit_mb: None,
+            cpu_limit_percent: None,
+            raise_fd_limit: false,
+            backend_nice: None,
+            max_concurrent_backend_commands: DEFAULT_MAX_CONCURRENT_BACKEND_COMMANDS,
+            readiness_timeout_ms: DEFAULT_READINESS_TIMEOUT_MS,
+            startup_timeout_ms: DEFAULT_STARTUP_TIMEOUT_MS,
+            platform_startup_timeout_ms: HashMap::new(),
+            first_launch_startup_timeout_ms: None,
+            first_run_startup_timeout_ms: DEFAULT_FIRST_RUN_STARTUP_TIMEOUT_MS,
+            version_check_timeout_ms: DEFAULT_VERSION_CHECK_TIMEOUT_MS,
+            backend_data_dir: None,
+            minimal_path: false,
+            backend_concurrency: None,
+            max_body_size_bytes: None,
+            devtools_on_fatal: false,
+            verbose_logging: false,
+        }
+    }
+}
+
+impl BackendSettings {
+    pub fn resource_limits(&self) -> crate::limits::ResourceLimits {
+        crate::limits::ResourceLimits {
+            memory_limit_mb: self.memory_limit_mb,
+            cpu_limit_percent: self.cpu_limit_percent,
+        }
+    }
+
+    // URL of a backend endpoint using the configured scheme and host
+    pub fn endpoint_url(&self, port: u16, path: &str) -> String {
+        crate::health::endpoint_url(&self.health_scheme, &self.health_host, port, path)
+    }
+
+    pub fn health_url(&self, port: u16) -> String {
+        self.endpoint_url(port, &self.health_path)
+    }
+
+    // Startup timeout for this platform; a first launch never gets less than the steady-state one,
+    // and the backend's first run (downloading models/dependencies) gets the longest
+    pub fn startup_timeout(&self, first_launch: bool, first_run: bool) -> std::time::Duration {
+        let steady = self
+            .platform_startup_timeout_ms
+            .get(std::env::consts::OS)
+            .copied()
+            .unwrap_or(self.startup_timeout_ms);
+        let ms = if first_launch {
+            self.first_launch_startup_timeout_ms
+                .unwrap_or(DEFAULT_FIRST_LAUNCH_STARTUP_TIMEOUT_MS)
+                .max(steady)
+        } else {
+            steady
+        };
+        let ms = if first_run {
+            ms.max(self.first_run_startup_timeout_ms)
+        } else {
+            ms
+        };
+        std::time::Duration::from_millis(ms)
+    }
+
+    pub fn version_check_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.version_check_timeout_ms.max(1))
+    }
+
+    // Reject the values the individual setters would reject, for whole-object updates
+    // Filesystem checks (data dir, backend sources) are left to the caller since they block
+    pub fn validate(&self) -> Result<(), String> {
+        if self.preferred_port == Some(0) {
+            return Err("Port must be greater than 0".to_string());
+        }
+        if self.python_runtime == PythonRuntime::System && self.backend_source_dir.is_none() {
+            return Err("The system runtime needs the backend source dir".to_string());
+        }
+        if self.python_runtime == PythonRuntime::External && self.external_command.is_empty() {
+            return Err("The external runtime needs a backend command".to_string());
+        }
+        validate_url_path(&self.health_path)?;
+        if let Some(path) = &self.shutdown_path {
+            validate_url_path(path)?;
+        }
+        if !SUPPORTED_SCHEMES.contains(&self.health_scheme.as_str()) {
+            return Err(format!("Unsupported scheme '{}', expected http or https", self.health_scheme));
+        }
+        if self.health_host.is_empty() || self.health_host.contains(['/', ' ']) {
+            return Err(format!("Invalid host: {}", self.health_host));
+        }
+        validate_log_limits(self.log_buffer_capacity, self.max_log_line_length)?;
+        if let Some(interval_ms) = self.log_batch_interval_ms {
+            if !(10..=5000).contains(&interval_ms) {
+                return Err(format!("Batch interval must be between 10 and 5000 ms: {}", interval_ms));
+            }
+        }
+        if let Some(platform) = self
+            .platform_args
+            .keys()
+            .chain(self.platform_startup_timeout_ms.keys())
+            .find(|platform| !SUPPORTED_PLATFORMS.contains(&platform.as_str()))
+        {
+            return Err(format!(
+                "Unknown platform '{}', expected one of {}",
+                platform,
+                SUPPORTED_PLATFORMS.join(", ")
+            ));
+        }
+        if let Some(percent) = self.cpu_limit_percent {
+            if !(1..=100).contains(&percent) {
+                return Err(format!("CPU limit must be between 1 and 100 percent: {}", percent));
+            }
+        }
+        if self.memory_limit_mb == Some(0) {
+            return Err("Memory limit must be greater than 0".to_string());
+        }
+        if let Some(nice) = self.backend_nice {
+            if !(0..=crate::limits::MAX_NICE).contains(&nice) {
+                return Err(format!("Niceness must be between 0 and {}: {}", crate::limits::MAX_NICE, nice));
+            }
+        }
+        if self.raise_fd_limit && cfg!(target_os = "windows") {
+            return Err("Raising the fd limit is not applicable on this platform".to_string());
+        }
+        if self.max_concurrent_backend_commands == 0 {
+            return Err("Command limit must be at least 1".to_string());
+        }
+        if self.readiness_timeout_ms == 0
+            || self.startup_timeout_ms == 0
+            || self.first_run_startup_timeout_ms == 0
+            || self.first_launch_startup_timeout_ms == Some(0)
+            || self.platform_startup_timeout_ms.values().any(|ms| *ms == 0)
+        {
+            return Err("Timeouts must be greater than 0".to_string());
+        }
+        if self.backend_concurrency == Some(0) {
+            return Err("Concurrency must be greater than 0".to_string());
+        }
+        if let Some(bytes) = self.max_body_size_bytes {
+            validate_body_size(bytes)?;
+        }
+        Ok(())
+    }
+
+    // Whether going from these settings to `other` only takes effect once the backend restarts,
+    // i.e. it changes how the process is spawned or what it is told at startup
+    pub fn requires_restart(&self, other: &BackendSettings) -> bool {
+        self.preferred_port != other.preferred_port
+            || self.python_runtime != other.python_runtime
+            || self.system_python_path != other.system_python_path
+            || self.backend_source_dir != other.backend_source_dir
+            || self.external_command != other.external_command
+            || self.health_host != other.health_host
+            || self.max_log_line_length != other.max_log_line_length
+            || self.log_batch_interval_ms != other.log_batch_interval_ms
+            || self.strip_ansi != other.strip_ansi
+            || sidecar_args(self) != sidecar_args(other)
+            || sidecar_env(self) != sidecar_env(other)
+            || self.memory_limit_mb != other.memory_limit_mb
+            || self.cpu_limit_percent != other.cpu_limit_percent
+            || self.raise_fd_limit != other.raise_fd_limit
+            || self.minimal_path != other.minimal_path
+            || self.keep_backend_alive_on_close != other.keep_backend_alive_on_close
+    }
+}
+
+pub type SharedSettings = Arc<Mutex<BackendSettings>>;
+
+fn settings_file(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .map_err(|e| format!("Failed to resolve app config dir: {}", e))
+}
+
+// Load settings from disk, falling back to defaults if the file is missing or unreadable
+pub fn load(app: &AppHandle) -> BackendSettings {
+    let Ok(path) = settings_file(app) else {
+        return BackendSettings::default();
+    };
+
+    std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+// Write settings to disk (via a temp file + rename so a crash can't leave a half-written file)
+pub fn save(app: &AppHandle, settings: &BackendSettings) -> Result<(), String> {
+    let path = settings_file(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+
+    let contents = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, contents)
+        .map_err(|e| format!("Failed to write settings: {}", e))?;
+    std::fs::rename(&tmp_path, &path)
+        .map_err(|e| format!("Failed to save settings: {}", e))?;
+
+    Ok(())
+}
+
+// Delete the settings file, returning whether there was one
+pub fn delete(app: &AppHandle) -> Result<bool, String> {
+    let path = settings_file(app)?;
+    match std::fs::remove_file(&path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(format!("Failed to delete settings: {}", e)),
+    }
+}
+
+// Parse a settings object that must name every setting and nothing else
+// Deserializing BackendSettings directly would fill omitted fields with their defaults (which
+// keeps older files loading), silently resetting whatever a caller left out
+pub fn from_complete_json(value: serde_json::Value) -> Result<BackendSettings, String> {
+    let serde_json::Value::Object(fields) = &value else {
+        return Err("Settings must be a JSON object".to_string());
+    };
+    let expected = match serde_json::to_value(BackendSettings::default()) {
+        Ok(serde_json::Value::Object(expected)) => expected,
+        _ => return Err("Failed to serialize default settings".to_string()),
+    };
+
+    let unknown: Vec<&str> = fields
+        .keys()
+        .filter(|key| !expected.contains_key(*key))
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!("Unknown settings: {}", unknown.join(", ")));
+    }
+    let missing: Vec<&str> = expected
+        .keys()
+        .filter(|key| !fields.contains_key(*key))
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Missing settings: {}", missing.join(", ")));
+    }
+
+    serde_json::from_value(value).map_err(|e| format!("Invalid settings: {}", e))
+}
+
+pub const SUPPORTED_PLATFORMS: [&str; 3] = ["windows", "macos", "linux"];
+
+// Extra args for the current platform: the common ones first, then the platform-specific ones,
+// then those derived from settings
+pub fn sidecar_args(settings: &BackendSettings) -> Vec<String> {
+    let mut args = settings.extra_args.clone();
+    if let Some(platform_args) = settings.platform_args.get(std::env::consts::OS) {
+        args.extend(platform_args.iter().cloned());
+    }
+    if let Some(concurrency) = settings.backend_concurrency {
+        args.push("--concurrency".to_string());
+        args.push(concurrency.to_string());
+    }
+    args
+}
+
+// Sidecar environment variables derived from the settings
+pub fn sidecar_env(settings: &BackendSettings) -> Vec<(&'static str, String)> {
+    let mut vars = Vec::new();
+    if let Some(data_dir) = &settings.backend_data_dir {
+        vars.push(("OWORK_DATA_DIR", data_dir.clone()));
+    }
+    if let Some(bytes) = settings.max_body_size_bytes {
+        vars.push(("OWORK_MAX_BODY_SIZE", bytes.to_string()));
+    }
+    // Ask the backend not to emit colors we would only strip again
+    if settings.strip_ansi {
+        vars.push(("NO_COLOR", "1".to_string()));
+        vars.push(("TERM", "dumb".to_string()));
+    }
+    vars
+}
+
+pub const MAX_LOG_BUFFER_CAPACITY: usize = 100_000;
+// Worst-case memory the log buffer may hold: every buffered line at the maximum length
+pub const MAX_LOG_BUFFER_BYTES: usize = 256 * 1024 * 1024;
+
+// The buffer keeps up to capacity lines of up to max_line_length bytes (0 = unlimited) each,
+// so bound their product; an unlimited line length is only allowed with the default capacity
+pub fn validate_log_limits(capacity: usize, max_line_length: usize) -> Result<(), String> {
+    if capacity > MAX_LOG_BUFFER_CAPACITY {
+        return Err(format!(
+            "Log buffer capacity must be at most {} lines: {}",
+            MAX_LOG_BUFFER_CAPACITY, capacity
+        ));
+    }
+    if max_line_length == 0 && capacity > DEFAULT_LOG_BUFFER_CAPACITY {
+        return Err(format!(
+            "An unlimited line length needs a log buffer of at most {} lines: {}",
+            DEFAULT_LOG_BUFFER_CAPACITY, capacity
+        ));
+    }
+    if capacity.saturating_mul(max_line_length) > MAX_LOG_BUFFER_BYTES {
+        return Err(format!(
+            "Log buffer of {} lines of up to {} bytes exceeds {} MB",
+            capacity,
+            max_line_length,
+            MAX_LOG_BUFFER_BYTES / (1024 * 1024)
+        ));
+    }
+    Ok(())
+}
+
+pub const MIN_BODY_SIZE_BYTES: u64 = 1024;
+pub const MAX_BODY_SIZE_BYTES: u64 = 10 * 1024 * 1024 * 1024;
+
+// Body size limits outside 1 KiB..10 GiB are almost certainly typos (e.g. MB given as bytes)
+pub fn validate_body_size(bytes: u64) -> Result<(), String> {
+    if !(MIN_BODY_SIZE_BYTES..=MAX_BODY_SIZE_BYTES).contains(&bytes) {
+        return Err(format!(
+            "Max body size must be between {} and {} bytes: {}",
+            MIN_BODY_SIZE_BYTES, MAX_BODY_SIZE_BYTES, bytes
+        ));
+    }
+    Ok(())
+}
+
+// Endpoint paths must be absolute URL paths, e.g. "/health" or "/api/ping"
+pub fn validate_url_path(path: &str) -> Result<(), String> {
+    if !path.starts_with('/') {
+        return Err(format!("Path must start with '/': {}", path));
+    }
+    if path.chars().any(|c| c.is_whitespace()) {
+        return Err(format!("Path must not contain whitespace: {}", path));
+    }
+    Ok(())
+}
+
+// A data dir must be absolute and writable; it is created if missing
+pub fn validate_data_dir(dir: &str) -> Result<(), String> {
+    let dir = std::path::Path::new(dir);
+    if !dir.is_absolute() {
+        return Err(format!("Data directory must be an absolute path: {}", dir.display()));
+    }
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let probe = dir.join(".owork-write-test");
+    std::fs::write(&probe, b"")
+        .map_err(|e| format!("Data directory is not writable: {}: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(probe);
+    Ok(())
+}
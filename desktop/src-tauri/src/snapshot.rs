@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+// File (inside the app data dir) recording the last backend we spawned
+const SNAPSHOT_FILE: &str = "last-backend.json";
+
+// Last-known backend process, kept on disk so a relaunched app can find a backend
+// that outlived a crashed or uncleanly exited previous instance
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BackendSnapshot {
+    pub pid: u32,
+    pub port: u16,
+    // Opaque process start time (see process::start_time), so a reused pid isn't mistaken for
+    // the backend; missing in snapshots from older versions
+    #[serde(default)]
+    pub start_time: Option<String>,
+}
+
+fn snapshot_file(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(SNAPSHOT_FILE))
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))
+}
+
+pub fn save(app: &AppHandle, snapshot: &BackendSnapshot) -> Result<(), String> {
+    let path = snapshot_file(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    }
+
+    let contents = serde_json::to_string(snapshot)
+        .map_err(|e| format!("Failed to serialize backend snapshot: {}", e))?;
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write backend snapshot: {}", e))
+}
+
+pub fn load(app: &AppHandle) -> Option<BackendSnapshot> {
+    let path = snapshot_file(app).ok()?;
+    let contents = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+// Forget the snapshot once the backend it describes is known to be gone
+pub fn clear(app: &AppHandle) {
+    if let Ok(path) = snapshot_file(app) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+// Marker file (inside the app data dir) written once the backend has started successfully
+const LAUNCHED_MARKER_FILE: &str = "launched-before";
+
+pub fn has_launched_before(app: &AppHandle) -> bool {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(LAUNCHED_MARKER_FILE).exists())
+        .unwrap_or(false)
+}
+
+pub fn clear_launched(app: &AppHandle) {
+    if let Ok(dir) = app.path().app_data_dir() {
+        let _ = std::fs::remove_file(dir.join(LAUNCHED_MARKER_FILE));
+    }
+}
+
+pub fn mark_launched(app: &AppHandle) -> Result<(), String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    std::fs::write(dir.join(LAUNCHED_MARKER_FILE), b"")
+        .map_err(|e| format!("Failed to write launch marker: {}", e))
+}
+
+// Marker written once the backend has completed its first run against a data dir.
+// Kept inside the data dir itself so switching to a fresh dir counts as a first run again
+const INITIALIZED_MARKER_FILE: &str = ".owork-initialized";
+
+fn initialized_marker(app: &AppHandle, data_dir: Option<&str>) -> Result<PathBuf, String> {
+    let dir = match data_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to resolve app data dir: {}", e))?,
+    };
+    Ok(dir.join(INITIALIZED_MARKER_FILE))
+}
+
+pub fn is_backend_initialized(app: &AppHandle, data_dir: Option<&str>) -> bool {
+    initialized_marker(app, data_dir)
+        .map(|path| path.exists())
+        .unwrap_or(false)
+}
+
+pub fn mark_backend_initialized(app: &AppHandle, data_dir: Option<&str>) -> Result<(), String> {
+    let path = initialized_marker(app, data_dir)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    }
+    std::fs::write(path, b"").map_err(|e| format!("Failed to write first-run marker: {}", e))
+}
+
+// Hash of the enhanced PATH computed on the previous launch
+const PATH_HASH_FILE: &str = "enhanced-path.sha256";
+
+// Store this launch's PATH hash, returning whether it differs from the previous launch's
+// (false on the first launch, when there is nothing to compare against)
+pub fn update_path_hash(app: &AppHandle, hash: &str) -> Result<bool, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))?;
+    let path = dir.join(PATH_HASH_FILE);
+    let changed = std::fs::read_to_string(&path)
+        .map(|previous| previous.trim() != hash)
+        .unwrap_or(false);
+
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    std::fs::write(&path, hash).map_err(|e| format!("Failed to write PATH hash: {}", e))?;
+    Ok(changed)
+}
+
+// Whether the bundled sidecar accepts --detachable, recorded as "<app version> <true|false>"
+// The sidecar ships with the app, so the answer holds until the app version changes
+const DETACH_SUPPORT_FILE: &str = "sidecar-detachable";
+
+pub fn load_detach_support(app: &AppHandle, version: &str) -> Option<bool> {
+    let dir = app.path().app_data_dir().ok()?;
+    let contents = std::fs::read_to_string(dir.join(DETACH_SUPPORT_FILE)).ok()?;
+    let (cached_version, supported) = contents.trim().split_once(' ')?;
+    if cached_version != version {
+        return None;
+    }
+    supported.parse().ok()
+}
+
+pub fn save_detach_support(app: &AppHandle, version: &str, supported: bool) -> Result<(), String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    std::fs::write(dir.join(DETACH_SUPPORT_FILE), format!("{} {}", version, supported))
+        .map_err(|e| format!("Failed to write sidecar detach support: {}", e))
+}
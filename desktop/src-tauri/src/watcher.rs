@@ -0,0 +1,77 @@
+// Development-only file watcher that restarts the backend when its Python sources change
+// Compiled into debug builds only and started for the system runtime's backend source dir;
+// the bundled sidecar is a frozen build, so restarting it on source changes would do nothing
+
+use notify::{RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+// Quiet period after the last change before restarting, so a save-all or git checkout
+// triggers a single restart
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+// Whether the backend is still run from `dir` by the system runtime (the runtime can be
+// switched while the app runs)
+async fn runs_from(app: &tauri::AppHandle, dir: &Path) -> bool {
+    let settings = app.state::<crate::SharedSettings>();
+    let settings = settings.lock().await;
+    settings.python_runtime == crate::settings::PythonRuntime::System
+        && settings.backend_source_dir.as_deref().map(Path::new) == Some(dir)
+}
+
+fn is_relevant(path: &Path) -> bool {
+    let in_cache = path
+        .components()
+        .any(|c| c.as_os_str() == "__pycache__" || c.as_os_str() == ".venv");
+    !in_cache && path.extension().map(|ext| ext == "py").unwrap_or(false)
+}
+
+pub fn start(app: tauri::AppHandle, dir: PathBuf) -> Result<(), String> {
+    let (tx, rx) = mpsc::channel::<PathBuf>();
+
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        if let Ok(event) = result {
+            if !event.kind.is_access() {
+                for path in event.paths.into_iter().filter(|path| is_relevant(path)) {
+                    let _ = tx.send(path);
+                }
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+    watcher
+        .watch(&dir, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+    log::info!("Watching {} for backend source changes", dir.display());
+
+    std::thread::spawn(move || {
+        // Keep the watcher alive for as long as this thread runs
+        let _watcher = watcher;
+
+        while let Ok(first) = rx.recv() {
+            let mut changed = vec![first];
+            while let Ok(path) = rx.recv_timeout(DEBOUNCE) {
+                changed.push(path);
+            }
+            changed.sort();
+            changed.dedup();
+
+            let app = app.clone();
+            let dir = dir.clone();
+            tauri::async_runtime::block_on(async move {
+                if !runs_from(&app, &dir).await {
+                    return;
+                }
+                let paths: Vec<String> = changed.iter().map(|p| p.display().to_string()).collect();
+                let _ = app.emit("backend-source-changed", paths);
+                if let Err(e) = crate::restart(&app).await {
+                    let _ = app.emit("backend-warning", format!("Restart after source change failed: {}", e));
+                }
+            });
+        }
+    });
+
+    Ok(())
+}